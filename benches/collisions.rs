@@ -0,0 +1,68 @@
+//! Measures how long a simulation tick takes with a dense asteroid field.
+//!
+//! Run with `cargo bench --bench collisions`.
+
+use std::{f32::consts::PI, time::Instant};
+
+use macroquad::prelude::*;
+use paper_space::{
+    asteroid::Asteroid,
+    input::PlayerInput,
+    rng::Rng,
+    simulation::{Simulation, TICK_DURATION},
+    tuning::Tuning,
+};
+
+const ASTEROIDS: usize = 5000;
+const FIELD_RADIUS: f32 = 9000.0;
+const ASTEROID_SPEED: f32 = 20.0;
+const WARMUP_TICKS: usize = 60;
+const MEASURED_TICKS: usize = 600;
+const FRAME_BUDGET: f32 = 1.0 / 60.0;
+
+/// Spreads small, slow asteroids evenly over a disc around the ship, far
+/// enough apart that the field does not destroy itself on the first tick.
+fn asteroid_field(simulation: &mut Simulation, rng: &mut Rng) {
+    let golden_angle = PI * (3.0 - 5.0f32.sqrt());
+    for i in 0..ASTEROIDS {
+        let distance = FIELD_RADIUS * ((i as f32 + 0.5) / ASTEROIDS as f32).sqrt();
+        let position = Vec2::from_angle(golden_angle * i as f32) * distance;
+        let velocity = Vec2::from_angle(rng.gen_range(0.0, PI * 2.0)) * ASTEROID_SPEED;
+        let size = rng.gen_range(1, 3);
+        simulation.add_asteroid(Asteroid::with_size(position, velocity, size, rng));
+    }
+}
+
+fn main() {
+    let mut rng = Rng::new(1);
+    let mut simulation = Simulation::new(1, Tuning::default());
+    asteroid_field(&mut simulation, &mut rng);
+
+    let input = PlayerInput {
+        fire_bullet: true,
+        launch_rocket: true,
+        spawn_drone: true,
+        spawn_mine: true,
+        turn_left: true,
+        ..Default::default()
+    };
+
+    for _ in 0..WARMUP_TICKS {
+        simulation.update(&input, TICK_DURATION);
+    }
+
+    let start = Instant::now();
+    for _ in 0..MEASURED_TICKS {
+        simulation.update(&input, TICK_DURATION);
+    }
+    let tick_time = start.elapsed().as_secs_f32() / MEASURED_TICKS as f32;
+    let ticks_per_frame = FRAME_BUDGET / TICK_DURATION;
+
+    println!(
+        "{} asteroids left, {:.3} ms per tick, {:.3} ms of simulation per 60 Hz frame ({:.0}% of budget)",
+        simulation.asteroids().len(),
+        tick_time * 1000.0,
+        tick_time * ticks_per_frame * 1000.0,
+        tick_time * ticks_per_frame / FRAME_BUDGET * 100.0
+    );
+}
@@ -0,0 +1,636 @@
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use macroquad::prelude::*;
+
+use crate::{
+    bindings::{Action, Bindings, Key},
+    game::Game,
+    high_scores::{HighScores, NameEntry},
+    menu::{draw_centered_text, draw_overlay, draw_title, Menu},
+    tuning::{Tuning, TuningWatcher},
+};
+
+const LAST_RUN_REPLAY_PATH: &str = "last_run.replay";
+const QUICK_SAVE_PATH: &str = "quicksave.ron";
+const TEXT_SIZE: f32 = 30.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TitleAction {
+    Play,
+    Settings,
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PauseAction {
+    Resume,
+    Restart,
+    Settings,
+    Title,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GameOverAction {
+    Restart,
+    Title,
+    Quit,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SettingsAction {
+    Fullscreen,
+    DebugInfo,
+    Controls,
+    Back,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ControlsAction {
+    Rebind(Action),
+    Reset,
+    Back,
+}
+
+/// An action waiting for the key to bind to it.
+pub struct Rebind {
+    action: Action,
+    replace: bool,
+}
+
+/// Options changed from the settings screen.
+pub struct Settings {
+    pub fullscreen: bool,
+    pub show_debug: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            fullscreen: true,
+            show_debug: true,
+        }
+    }
+}
+
+/// What the run looked like when it ended.
+pub struct Summary {
+    points: u32,
+    seed: u64,
+}
+
+pub enum State {
+    Title(Menu<TitleAction>),
+    Playing,
+    Paused(Menu<PauseAction>),
+    GameOver {
+        summary: Summary,
+        name_entry: Option<NameEntry>,
+        menu: Menu<GameOverAction>,
+    },
+    /// Settings remember the state they were opened from, to go back to it.
+    Settings {
+        menu: Menu<SettingsAction>,
+        return_to: Box<State>,
+    },
+    Controls {
+        menu: Menu<ControlsAction>,
+        rebind: Option<Rebind>,
+        return_to: Box<State>,
+    },
+}
+
+fn title_menu() -> Menu<TitleAction> {
+    Menu::new(vec![
+        ("Play".to_string(), TitleAction::Play),
+        ("Settings".to_string(), TitleAction::Settings),
+        ("Quit".to_string(), TitleAction::Quit),
+    ])
+}
+
+fn pause_menu() -> Menu<PauseAction> {
+    Menu::new(vec![
+        ("Resume".to_string(), PauseAction::Resume),
+        ("Restart".to_string(), PauseAction::Restart),
+        ("Settings".to_string(), PauseAction::Settings),
+        ("Quit to title".to_string(), PauseAction::Title),
+    ])
+}
+
+fn game_over_menu() -> Menu<GameOverAction> {
+    Menu::new(vec![
+        ("Play again".to_string(), GameOverAction::Restart),
+        ("Title".to_string(), GameOverAction::Title),
+        ("Quit".to_string(), GameOverAction::Quit),
+    ])
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn settings_menu(settings: &Settings) -> Menu<SettingsAction> {
+    Menu::new(vec![
+        (
+            format!("Fullscreen: {}", on_off(settings.fullscreen)),
+            SettingsAction::Fullscreen,
+        ),
+        (
+            format!("Debug info: {}", on_off(settings.show_debug)),
+            SettingsAction::DebugInfo,
+        ),
+        ("Controls".to_string(), SettingsAction::Controls),
+        ("Back".to_string(), SettingsAction::Back),
+    ])
+}
+
+fn controls_menu(bindings: &Bindings) -> Menu<ControlsAction> {
+    let mut items: Vec<_> = Action::ALL
+        .iter()
+        .map(|action| {
+            (
+                format!("{}: {}", action.name(), bindings.label(*action)),
+                ControlsAction::Rebind(*action),
+            )
+        })
+        .collect();
+    items.push(("Reset to defaults".to_string(), ControlsAction::Reset));
+    items.push(("Back".to_string(), ControlsAction::Back));
+    Menu::new(items)
+}
+
+fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+fn handle_playback_keys(game: &mut Game) {
+    let Some(playback) = game.playback_mut() else {
+        return;
+    };
+    if is_key_pressed(KeyCode::Space) {
+        playback.toggle_pause();
+    }
+    if is_key_pressed(KeyCode::Period) {
+        playback.step();
+    }
+    if is_key_pressed(KeyCode::Key1) {
+        playback.set_speed(1.0);
+    }
+    if is_key_pressed(KeyCode::Key2) {
+        playback.set_speed(2.0);
+    }
+    if is_key_pressed(KeyCode::Key4) {
+        playback.set_speed(4.0);
+    }
+}
+
+fn handle_save_keys(game: &mut Game) {
+    if game.is_playback() {
+        return;
+    }
+    if is_key_pressed(KeyCode::F5) {
+        if let Err(error) = game.save(QUICK_SAVE_PATH) {
+            eprintln!("Failed to save game to {}: {}", QUICK_SAVE_PATH, error);
+        }
+    }
+    if is_key_pressed(KeyCode::F9) {
+        if let Err(error) = game.load(QUICK_SAVE_PATH) {
+            eprintln!("Failed to load game from {}: {}", QUICK_SAVE_PATH, error);
+        }
+    }
+}
+
+/// Everything around a game: menus, high scores, tuning reloads.
+pub struct App {
+    game: Game,
+    state: State,
+    settings: Settings,
+    bindings: Bindings,
+    bindings_path: Option<PathBuf>,
+    tuning: Tuning,
+    tuning_watcher: TuningWatcher,
+    high_scores: HighScores,
+    high_scores_path: Option<PathBuf>,
+    fixed_seed: Option<u64>,
+    is_running: bool,
+}
+
+impl App {
+    /// Opens on the title screen; `--seed` is passed as `fixed_seed` to make
+    /// every new game start from it.
+    pub fn new(tuning: Tuning, tuning_watcher: TuningWatcher, fixed_seed: Option<u64>) -> Self {
+        let high_scores_path = HighScores::default_path();
+        let high_scores = match high_scores_path.as_ref().map(HighScores::load) {
+            Some(Ok(high_scores)) => high_scores,
+            Some(Err(error)) => {
+                eprintln!("Failed to load high scores: {}", error);
+                HighScores::default()
+            }
+            None => HighScores::default(),
+        };
+        let bindings_path = Bindings::default_path();
+        let bindings = match bindings_path.as_ref().map(Bindings::load) {
+            Some(Ok(bindings)) => bindings,
+            Some(Err(error)) => {
+                eprintln!("Failed to load key bindings: {}", error);
+                Bindings::default()
+            }
+            None => Bindings::default(),
+        };
+        Self {
+            game: Game::new(fixed_seed.unwrap_or_else(random_seed), tuning.clone()),
+            state: State::Title(title_menu()),
+            settings: Settings::default(),
+            bindings,
+            bindings_path,
+            tuning,
+            tuning_watcher,
+            high_scores,
+            high_scores_path,
+            fixed_seed,
+            is_running: true,
+        }
+    }
+
+    /// Skips the title screen and plays the given game right away, e.g. a replay.
+    pub fn with_game(mut self, game: Game) -> Self {
+        self.game = game;
+        self.game.set_show_debug(self.settings.show_debug);
+        self.state = State::Playing;
+        self
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.is_running
+    }
+
+    fn new_game(&mut self) {
+        self.game = Game::new(
+            self.fixed_seed.unwrap_or_else(random_seed),
+            self.tuning.clone(),
+        );
+        self.game.set_show_debug(self.settings.show_debug);
+    }
+
+    fn reload_tuning(&mut self) {
+        match self.tuning_watcher.poll() {
+            Some(Ok(tuning)) => {
+                self.tuning = tuning.clone();
+                let was_recording = self.game.recording().is_some();
+                if !self.game.set_tuning(tuning) {
+                    self.game.print(
+                        "Tuning reloaded for the next game; the replay keeps its own.".to_string(),
+                    );
+                } else if was_recording {
+                    self.game
+                        .print("Tuning reloaded; this run is no longer recorded.".to_string());
+                } else {
+                    self.game.print("Tuning reloaded.".to_string());
+                }
+            }
+            Some(Err(error)) => {
+                self.game.print(format!("Tuning not reloaded: {}", error));
+            }
+            None => {}
+        }
+    }
+
+    fn save_high_scores(&self) {
+        if let Some(Err(error)) = self
+            .high_scores_path
+            .as_ref()
+            .map(|path| self.high_scores.save(path))
+        {
+            eprintln!("Failed to save high scores: {}", error);
+        }
+    }
+
+    fn save_bindings(&self) {
+        if let Some(Err(error)) = self
+            .bindings_path
+            .as_ref()
+            .map(|path| self.bindings.save(path))
+        {
+            eprintln!("Failed to save key bindings: {}", error);
+        }
+    }
+
+    /// Keeps the recording of the current run, however it ended.
+    fn save_replay(&self) {
+        if self.game.is_playback() {
+            return;
+        }
+        if let Some(Err(error)) = self
+            .game
+            .recording()
+            .map(|replay| replay.save(LAST_RUN_REPLAY_PATH))
+        {
+            eprintln!("Failed to save replay {}: {}", LAST_RUN_REPLAY_PATH, error);
+        }
+    }
+
+    /// Ends the run: keeps its replay and asks for a name if it made the table.
+    fn finish_run(&mut self) -> State {
+        let points = self.game.score();
+        let mut name_entry = None;
+        self.save_replay();
+        if !self.game.is_playback() && self.high_scores.qualifies(points) {
+            // Characters typed while playing would otherwise end up in the name.
+            clear_input_queue();
+            name_entry = Some(NameEntry::new(points));
+        }
+        State::GameOver {
+            summary: Summary {
+                points,
+                seed: self.game.seed(),
+            },
+            name_entry,
+            menu: game_over_menu(),
+        }
+    }
+
+    fn update_title(&mut self, menu: &mut Menu<TitleAction>) -> Option<State> {
+        if is_key_pressed(KeyCode::Escape) {
+            self.is_running = false;
+            return None;
+        }
+        match menu.update()? {
+            TitleAction::Play => {
+                self.new_game();
+                Some(State::Playing)
+            }
+            TitleAction::Settings => Some(State::Settings {
+                menu: settings_menu(&self.settings),
+                return_to: Box::new(State::Title(title_menu())),
+            }),
+            TitleAction::Quit => {
+                self.is_running = false;
+                None
+            }
+        }
+    }
+
+    fn update_playing(&mut self) -> Option<State> {
+        if is_key_pressed(KeyCode::Escape) {
+            return Some(State::Paused(pause_menu()));
+        }
+        handle_playback_keys(&mut self.game);
+        handle_save_keys(&mut self.game);
+        self.game.update(&self.bindings.read_input());
+        if self.game.is_game_over() {
+            return Some(self.finish_run());
+        }
+        None
+    }
+
+    fn update_paused(&mut self, menu: &mut Menu<PauseAction>) -> Option<State> {
+        if is_key_pressed(KeyCode::Escape) {
+            return Some(State::Playing);
+        }
+        match menu.update()? {
+            PauseAction::Resume => Some(State::Playing),
+            PauseAction::Restart => {
+                self.save_replay();
+                self.new_game();
+                Some(State::Playing)
+            }
+            PauseAction::Settings => Some(State::Settings {
+                menu: settings_menu(&self.settings),
+                return_to: Box::new(State::Paused(pause_menu())),
+            }),
+            PauseAction::Title => {
+                self.save_replay();
+                Some(State::Title(title_menu()))
+            }
+        }
+    }
+
+    fn update_game_over(
+        &mut self,
+        name_entry: &mut Option<NameEntry>,
+        menu: &mut Menu<GameOverAction>,
+    ) -> Option<State> {
+        if let Some(entry) = name_entry {
+            if is_key_pressed(KeyCode::Escape) {
+                *name_entry = None;
+            } else if let Some(name) = entry.update() {
+                self.high_scores.insert(name, entry.points());
+                self.save_high_scores();
+                *name_entry = None;
+            }
+            return None;
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            return Some(State::Title(title_menu()));
+        }
+        match menu.update()? {
+            GameOverAction::Restart => {
+                self.new_game();
+                Some(State::Playing)
+            }
+            GameOverAction::Title => Some(State::Title(title_menu())),
+            GameOverAction::Quit => {
+                self.is_running = false;
+                None
+            }
+        }
+    }
+
+    fn update_settings(
+        &mut self,
+        menu: &mut Menu<SettingsAction>,
+        return_to: &mut Box<State>,
+    ) -> Option<State> {
+        let action = if is_key_pressed(KeyCode::Escape) {
+            SettingsAction::Back
+        } else {
+            menu.update()?
+        };
+        match action {
+            SettingsAction::Fullscreen => {
+                self.settings.fullscreen = !self.settings.fullscreen;
+                set_fullscreen(self.settings.fullscreen);
+            }
+            SettingsAction::DebugInfo => {
+                self.settings.show_debug = !self.settings.show_debug;
+                self.game.set_show_debug(self.settings.show_debug);
+            }
+            SettingsAction::Controls => {
+                let settings = State::Settings {
+                    menu: settings_menu(&self.settings).with_selected(action),
+                    return_to: std::mem::replace(return_to, Box::new(State::Playing)),
+                };
+                return Some(State::Controls {
+                    menu: controls_menu(&self.bindings),
+                    rebind: None,
+                    return_to: Box::new(settings),
+                });
+            }
+            SettingsAction::Back => {
+                return Some(std::mem::replace(
+                    return_to.as_mut(),
+                    State::Title(title_menu()),
+                ));
+            }
+        }
+        *menu = settings_menu(&self.settings).with_selected(action);
+        None
+    }
+
+    fn update_controls(
+        &mut self,
+        menu: &mut Menu<ControlsAction>,
+        rebind: &mut Option<Rebind>,
+        return_to: &mut Box<State>,
+    ) -> Option<State> {
+        if let Some(Rebind { action, replace }) = *rebind {
+            if is_key_pressed(KeyCode::Escape) {
+                *rebind = None;
+            } else if let Some(key) = get_last_key_pressed()
+                .map(Key)
+                .filter(|key| key.name().is_some())
+            {
+                self.bindings.bind(action, key, replace);
+                self.save_bindings();
+                *menu = controls_menu(&self.bindings).with_selected(ControlsAction::Rebind(action));
+                *rebind = None;
+            }
+            return None;
+        }
+
+        if is_key_pressed(KeyCode::Escape) {
+            return Some(std::mem::replace(return_to.as_mut(), State::Playing));
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            if let Some(ControlsAction::Rebind(action)) = menu.selected() {
+                *rebind = Some(Rebind {
+                    action,
+                    replace: false,
+                });
+                return None;
+            }
+        }
+        match menu.update()? {
+            ControlsAction::Rebind(action) => {
+                *rebind = Some(Rebind {
+                    action,
+                    replace: true,
+                });
+            }
+            ControlsAction::Reset => {
+                self.bindings = Bindings::default();
+                self.save_bindings();
+                *menu = controls_menu(&self.bindings).with_selected(ControlsAction::Reset);
+            }
+            ControlsAction::Back => {
+                return Some(std::mem::replace(return_to.as_mut(), State::Playing));
+            }
+        }
+        None
+    }
+
+    pub fn update(&mut self) {
+        self.reload_tuning();
+
+        let mut state = std::mem::replace(&mut self.state, State::Playing);
+        let next = match &mut state {
+            State::Title(menu) => self.update_title(menu),
+            State::Playing => self.update_playing(),
+            State::Paused(menu) => self.update_paused(menu),
+            State::GameOver {
+                name_entry, menu, ..
+            } => self.update_game_over(name_entry, menu),
+            State::Settings { menu, return_to } => self.update_settings(menu, return_to),
+            State::Controls {
+                menu,
+                rebind,
+                return_to,
+            } => self.update_controls(menu, rebind, return_to),
+        };
+        self.state = next.unwrap_or(state);
+    }
+
+    pub fn draw(&self) {
+        let top = screen_height() / 4.0;
+        match &self.state {
+            State::Title(menu) => {
+                clear_background(LIGHTGRAY);
+                draw_title("Paper Space", top);
+                menu.draw(top + TEXT_SIZE * 2.0);
+                self.high_scores.draw(top + TEXT_SIZE * 3.0 + menu.height());
+            }
+            State::Playing => self.game.draw(&self.bindings),
+            State::Paused(menu) => {
+                self.game.draw(&self.bindings);
+                draw_overlay();
+                draw_title("Paused", top);
+                menu.draw(top + TEXT_SIZE * 2.0);
+            }
+            State::GameOver {
+                summary,
+                name_entry,
+                menu,
+            } => {
+                self.game.draw(&self.bindings);
+                draw_overlay();
+                if let Some(entry) = name_entry {
+                    entry.draw(&self.high_scores);
+                    return;
+                }
+                draw_title("Game over", top);
+                draw_centered_text(
+                    &format!("Score: {}   Seed: {}", summary.points, summary.seed),
+                    top + TEXT_SIZE * 1.5,
+                    TEXT_SIZE,
+                );
+                menu.draw(top + TEXT_SIZE * 3.0);
+                self.high_scores.draw(top + TEXT_SIZE * 4.0 + menu.height());
+            }
+            State::Settings { menu, return_to } => {
+                self.draw_background(return_to);
+                draw_title("Settings", top);
+                menu.draw(top + TEXT_SIZE * 2.0);
+            }
+            State::Controls {
+                menu,
+                rebind,
+                return_to,
+            } => {
+                self.draw_background(return_to);
+                draw_title("Controls", top);
+                let hint = match rebind {
+                    Some(rebind) => {
+                        format!("Press a key for {} (Escape - Cancel)", rebind.action.name())
+                    }
+                    None => "Enter - Replace key, Tab - Add key".to_string(),
+                };
+                draw_centered_text(&hint, top + TEXT_SIZE * 1.5, TEXT_SIZE);
+                menu.draw(top + TEXT_SIZE * 3.0);
+            }
+        }
+    }
+
+    /// Backdrop for a screen opened from `state`: the paused game if there
+    /// is one underneath, otherwise a blank screen.
+    fn draw_background(&self, state: &State) {
+        match state {
+            State::Title(_) => clear_background(LIGHTGRAY),
+            State::Settings { return_to, .. } | State::Controls { return_to, .. } => {
+                self.draw_background(return_to)
+            }
+            _ => {
+                self.game.draw(&self.bindings);
+                draw_overlay();
+            }
+        }
+    }
+}
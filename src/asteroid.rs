@@ -1,46 +1,133 @@
 use std::f32::consts::PI;
 
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{entity::EntityId, events::DestroyCause, geometry, rng::Rng, tuning::AsteroidTuning};
 
 const MAX_MOVE_SPEED: f32 = 100.0;
 const MAX_ROTATION_SPEED: f32 = 3.0;
+const MAX_SPIN_SPEED: f32 = 10.0;
+const FRAGMENT_GAP: f32 = 5.0;
+const MIN_VERTICES: usize = 7;
+const VERTICES_PER_SIZE: usize = 2;
+const JAGGEDNESS: f32 = 0.3;
+const VERTEX_ANGLE_JITTER: f32 = 0.3;
 const MIN_RADIUS: f32 = 25.0;
-const MAX_ASTEROID_SIZE: u8 = 5;
+pub const MAX_ASTEROID_SIZE: u8 = 5;
+pub const MAX_RADIUS: f32 = MAX_ASTEROID_SIZE as f32 * MIN_RADIUS;
+const PLATING_SCALE: f32 = 0.7;
+const CORE_RADIUS: f32 = 6.0;
+
+/// What an asteroid is made of, deciding how it takes hits and breaks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Material {
+    #[default]
+    Rock,
+    /// Takes several bullet hits and ignores drone fire.
+    Metallic,
+    /// Shatters into many fast shards.
+    Ice,
+    /// Blows up when destroyed, taking its neighbours with it.
+    Explosive,
+    /// Drops supply crates.
+    Crystalline,
+}
+
+impl Material {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Material::Rock => "rock",
+            Material::Metallic => "metallic",
+            Material::Ice => "ice",
+            Material::Explosive => "explosive",
+            Material::Crystalline => "crystalline",
+        }
+    }
 
-#[derive(Clone)]
+    fn color(&self) -> Color {
+        match self {
+            Material::Rock => BLACK,
+            Material::Metallic => DARKGRAY,
+            Material::Ice => SKYBLUE,
+            Material::Explosive => RED,
+            Material::Crystalline => PURPLE,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Asteroid {
+    /// Assigned when the asteroid enters the simulation.
+    id: EntityId,
     position: Vec2,
+    previous_position: Vec2,
     velocity: Vec2,
     angle: f32,
+    previous_angle: f32,
     rotation_speed: f32,
     size: u8,
+    outline: Vec<Vec2>,
+    material: Material,
+    hit_points: u32,
     is_alive: bool,
 }
 
+/// Jagged outline around the origin, no point further out than `radius`.
+fn generate_outline(size: u8, seed: u64) -> Vec<Vec2> {
+    let mut rng = Rng::new(seed);
+    let radius = size as f32 * MIN_RADIUS;
+    let count = MIN_VERTICES + size as usize * VERTICES_PER_SIZE;
+    let step = PI * 2.0 / count as f32;
+    (0..count)
+        .map(|i| {
+            let angle =
+                step * (i as f32 + rng.gen_range(-VERTEX_ANGLE_JITTER, VERTEX_ANGLE_JITTER));
+            Vec2::from_angle(angle) * radius * rng.gen_range(1.0 - JAGGEDNESS, 1.0)
+        })
+        .collect()
+}
+
 impl Asteroid {
-    pub fn new(position: Vec2, velocity: Vec2) -> Self {
-        Self {
-            position,
-            velocity: velocity * MAX_MOVE_SPEED,
-            angle: 0.0,
-            rotation_speed: rand::gen_range(-MAX_ROTATION_SPEED, MAX_ROTATION_SPEED),
-            size: rand::gen_range(1, MAX_ASTEROID_SIZE),
-            is_alive: true,
-        }
+    pub fn new(position: Vec2, velocity: Vec2, rng: &mut Rng) -> Self {
+        let size = rng.gen_range(1, MAX_ASTEROID_SIZE);
+        Self::with_size(position, velocity * MAX_MOVE_SPEED, size, rng)
     }
 
-    pub fn new_smaller(position: Vec2, velocity: Vec2, size: u8) -> Self {
+    pub fn with_size(position: Vec2, velocity: Vec2, size: u8, rng: &mut Rng) -> Self {
         Self {
+            id: EntityId::default(),
             position,
+            previous_position: position,
             velocity,
             angle: 0.0,
-            rotation_speed: rand::gen_range(-MAX_ROTATION_SPEED, MAX_ROTATION_SPEED),
+            previous_angle: 0.0,
+            rotation_speed: rng.gen_range(-MAX_ROTATION_SPEED, MAX_ROTATION_SPEED),
             size,
+            outline: generate_outline(size, rng.next_u32() as u64),
+            material: Material::Rock,
+            hit_points: 1,
             is_alive: true,
         }
     }
 
+    pub fn with_material(mut self, material: Material, tuning: &AsteroidTuning) -> Self {
+        self.material = material;
+        self.hit_points = match material {
+            Material::Metallic => tuning.metallic_hit_points,
+            _ => 1,
+        };
+        self
+    }
+
+    pub fn with_id(mut self, id: EntityId) -> Self {
+        self.id = id;
+        self
+    }
+
     pub fn update(&mut self, dt: f32) {
+        self.previous_position = self.position;
+        self.previous_angle = self.angle;
         self.position += self.velocity * dt;
         self.angle += self.rotation_speed * dt;
     }
@@ -49,51 +136,217 @@ impl Asteroid {
         self.is_alive
     }
 
-    pub fn draw(&self) {
-        draw_poly_lines(
-            self.position.x,
-            self.position.y,
-            self.size * 5,
-            self.radius(),
-            self.angle.to_degrees(),
-            2.0,
-            BLACK,
-        );
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+        let angle = self.previous_angle + (self.angle - self.previous_angle) * alpha;
+        let color = self.material.color();
+        let outline = geometry::transform(&self.outline, position, angle);
+        let edges = outline.iter().zip(outline.iter().cycle().skip(1));
+        for (start, end) in edges.clone() {
+            draw_line(start.x, start.y, end.x, end.y, 2.0, color);
+        }
+        match self.material {
+            Material::Rock => {}
+            Material::Metallic => {
+                for (start, end) in edges {
+                    let start = position + (*start - position) * PLATING_SCALE;
+                    let end = position + (*end - position) * PLATING_SCALE;
+                    draw_line(start.x, start.y, end.x, end.y, 1.0, color);
+                }
+            }
+            Material::Ice => {
+                for point in &outline {
+                    draw_line(position.x, position.y, point.x, point.y, 1.0, color);
+                }
+            }
+            Material::Explosive => {
+                draw_circle(
+                    position.x,
+                    position.y,
+                    CORE_RADIUS * self.size as f32,
+                    color,
+                );
+            }
+            Material::Crystalline => {
+                for (start, end) in outline.iter().zip(outline.iter().cycle().skip(2)) {
+                    draw_line(start.x, start.y, end.x, end.y, 1.0, color);
+                }
+            }
+        }
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.id
     }
 
     pub fn position(&self) -> Vec2 {
         self.position
     }
 
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
     pub fn radius(&self) -> f32 {
         self.size as f32 * MIN_RADIUS
     }
 
+    pub fn material(&self) -> Material {
+        self.material
+    }
+
+    /// Whether a bullet fired by `cause` breaks the asteroid: metallic ones
+    /// soak several hits and shrug off drone fire.
+    pub fn bullet_hit(&mut self, cause: DestroyCause) -> bool {
+        if self.material != Material::Metallic {
+            return true;
+        }
+        if cause == DestroyCause::Drone {
+            return false;
+        }
+        self.hit_points = self.hit_points.saturating_sub(1);
+        self.hit_points == 0
+    }
+
+    /// Moves the asteroid clear of the circle around `center` and sends it
+    /// away from there at its current speed.
+    pub fn push_away(&mut self, center: Vec2, distance: f32) {
+        let direction = (self.position - center).try_normalize().unwrap_or(Vec2::X);
+        self.position = center + direction * (distance + self.radius());
+        self.previous_position = self.position;
+        self.velocity = direction * self.velocity.length();
+    }
+
     pub fn despawn(&mut self) {
         self.is_alive = false;
     }
 
-    pub fn destroy(&mut self, new_asteroids: &mut Vec<Asteroid>) {
+    /// Splits the asteroid into pieces one size smaller of the same material
+    /// that keep its momentum, scatter outwards and are pushed along
+    /// `impact`, the direction of whatever hit it. Most asteroids break into
+    /// two or three pieces, ice shatters into more and faster ones and
+    /// explosive ones leave nothing behind. Returns `false` if it was
+    /// already destroyed.
+    pub fn destroy(
+        &mut self,
+        impact: Vec2,
+        tuning: &AsteroidTuning,
+        new_asteroids: &mut Vec<Asteroid>,
+        rng: &mut Rng,
+    ) -> bool {
         if !self.is_alive {
-            return;
+            return false;
         }
         self.is_alive = false;
-        if self.size == 1 {
-            return;
+        if self.size == 1 || self.material == Material::Explosive {
+            return true;
         }
-        let count = self.size;
-        let angle_offset = rand::gen_range(0.0, PI * 2.0);
+        let size = self.size - 1;
+        let (count, speed) = if self.material == Material::Ice {
+            (
+                tuning.ice_shards,
+                tuning.fragment_speed * tuning.ice_shard_speed_multiplier,
+            )
+        } else if rng.next_f32() < 0.5 {
+            (2, tuning.fragment_speed)
+        } else {
+            (3, tuning.fragment_speed)
+        };
+        // Far enough from the center that neighbouring pieces don't touch.
+        let fragment_radius = size as f32 * MIN_RADIUS;
+        let distance = (fragment_radius + FRAGMENT_GAP) / (PI / count as f32).sin();
+        let push = impact.normalize_or_zero() * tuning.impact_speed;
+        let angle_offset = rng.gen_range(0.0, PI * 2.0);
         for i in 0..count {
-            let angle = angle_offset + (PI * 2.0) / MAX_ASTEROID_SIZE as f32 * i as f32;
-            let position =
-                self.position + Vec2::from_angle(angle) * (count as f32 * MIN_RADIUS);
-            let velocity = (position - self.position).normalize() * MAX_MOVE_SPEED;
-            new_asteroids.push(Asteroid::new_smaller(position, velocity, 1));
+            let direction = Vec2::from_angle(angle_offset + PI * 2.0 / count as f32 * i as f32);
+            new_asteroids.push(
+                Asteroid::with_size(
+                    self.position + direction * distance,
+                    self.velocity + direction * speed + push,
+                    size,
+                    rng,
+                )
+                .with_material(self.material, tuning),
+            );
         }
+        true
+    }
+
+    /// Outline at the asteroid's current position and rotation.
+    pub fn outline(&self) -> Vec<Vec2> {
+        geometry::transform(&self.outline, self.position, self.angle)
     }
 
     pub fn collision(&self, other: &Self) -> bool {
-        self.position.distance(other.position) <= self.size as f32 * MIN_RADIUS * 2.0
+        self.position.distance(other.position) <= self.radius() + other.radius()
+            && geometry::polygons_intersect(&self.outline(), &other.outline())
+    }
+
+    pub fn circle_collision(&self, center: Vec2, radius: f32) -> bool {
+        self.position.distance(center) <= self.radius() + radius
+            && geometry::polygon_circle(&self.outline(), center, radius)
+    }
+
+    /// Precise check against a shape already known to be close by.
+    pub fn polygon_collision(&self, polygon: &[Vec2]) -> bool {
+        geometry::polygons_intersect(&self.outline(), polygon)
+    }
+
+    /// Mass grows with the area of the asteroid.
+    pub fn mass(&self) -> f32 {
+        (self.size as f32).powi(2)
+    }
+
+    fn moment_of_inertia(&self) -> f32 {
+        0.5 * self.mass() * self.radius().powi(2)
+    }
+
+    /// Kinetic energy of the closing motion along the line between the
+    /// centers; zero if the asteroids are already moving apart.
+    pub fn impact_energy(&self, other: &Self) -> f32 {
+        let normal = (other.position - self.position).normalize_or_zero();
+        let closing_speed = (self.velocity - other.velocity).dot(normal).max(0.0);
+        let reduced_mass = self.mass() * other.mass() / (self.mass() + other.mass());
+        0.5 * reduced_mass * closing_speed.powi(2)
+    }
+
+    /// Pushes two overlapping asteroids apart and exchanges momentum between
+    /// them, turning part of the sliding motion at the contact into spin.
+    pub fn bounce(&mut self, other: &mut Self, tuning: &AsteroidTuning) {
+        let offset = other.position - self.position;
+        let normal = offset.try_normalize().unwrap_or(Vec2::X);
+        let tangent = normal.perp();
+        let inverse_mass = 1.0 / self.mass();
+        let other_inverse_mass = 1.0 / other.mass();
+        let total_inverse_mass = inverse_mass + other_inverse_mass;
+
+        let overlap = self.radius() + other.radius() - offset.length();
+        if overlap > 0.0 {
+            self.position -= normal * overlap * inverse_mass / total_inverse_mass;
+            other.position += normal * overlap * other_inverse_mass / total_inverse_mass;
+        }
+
+        let relative_velocity = other.velocity - self.velocity;
+        let normal_speed = relative_velocity.dot(normal);
+        if normal_speed >= 0.0 {
+            return;
+        }
+        let impulse = -(1.0 + tuning.restitution) * normal_speed / total_inverse_mass;
+        self.velocity -= normal * impulse * inverse_mass;
+        other.velocity += normal * impulse * other_inverse_mass;
+
+        let sliding_speed = relative_velocity.dot(tangent)
+            - self.rotation_speed * self.radius()
+            - other.rotation_speed * other.radius();
+        let friction = tuning.spin_transfer * sliding_speed / total_inverse_mass;
+        self.velocity += tangent * friction * inverse_mass;
+        other.velocity -= tangent * friction * other_inverse_mass;
+        self.rotation_speed = (self.rotation_speed
+            + friction * self.radius() / self.moment_of_inertia())
+        .clamp(-MAX_SPIN_SPEED, MAX_SPIN_SPEED);
+        other.rotation_speed = (other.rotation_speed
+            + friction * other.radius() / other.moment_of_inertia())
+        .clamp(-MAX_SPIN_SPEED, MAX_SPIN_SPEED);
     }
 
     pub fn size(&self) -> u8 {
@@ -0,0 +1,63 @@
+use macroquad::prelude::*;
+
+use crate::events::{GameEvent, GameEventListener};
+
+const TEXT_SIZE: f32 = 80.0;
+const DURATION: f32 = 2.5;
+const FADE_TIME: f32 = 0.5;
+
+/// Big announcement in the middle of the screen, e.g. "Wave 3".
+#[derive(Default)]
+pub struct Banner {
+    text: String,
+    time_left: f32,
+}
+
+impl Banner {
+    pub fn new() -> Self {
+        Self {
+            text: String::new(),
+            time_left: 0.0,
+        }
+    }
+
+    pub fn show(&mut self, text: String) {
+        self.text = text;
+        self.time_left = DURATION;
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.time_left = (self.time_left - dt).max(0.0);
+    }
+
+    pub fn draw(&self) {
+        if self.time_left <= 0.0 {
+            return;
+        }
+        let alpha = (self.time_left / FADE_TIME).min(1.0);
+        draw_centered_text_colored(&self.text, screen_height() / 3.0, alpha);
+    }
+}
+
+fn draw_centered_text_colored(text: &str, y: f32, alpha: f32) {
+    let dimensions = measure_text(text, None, TEXT_SIZE as u16, 1.0);
+    draw_text(
+        text,
+        (screen_width() - dimensions.width) / 2.0,
+        y,
+        TEXT_SIZE,
+        Color::new(0.0, 0.0, 0.0, alpha),
+    );
+}
+
+impl GameEventListener for Banner {
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::WaveStarted { number } => self.show(format!("Wave {}", number)),
+            GameEvent::BossSpawned { .. } => self.show("Boss incoming".to_string()),
+            GameEvent::BossPhaseChanged { phase, .. } => self.show(format!("Boss phase {}", phase)),
+            GameEvent::BossDestroyed { .. } => self.show("Boss destroyed".to_string()),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,355 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use macroquad::prelude::*;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::input::PlayerInput;
+
+/// Every key that can be bound, with the name used for it in the bindings
+/// file. Keys the game already uses while playing are left out: Escape for
+/// the pause menu, F5 and F9 for quick save and load, and Space, Period, 1, 2
+/// and 4 for replay controls.
+const KEYS: &[(KeyCode, &str)] = &[
+    (KeyCode::Apostrophe, "Apostrophe"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Key0, "Key0"),
+    (KeyCode::Key3, "Key3"),
+    (KeyCode::Key5, "Key5"),
+    (KeyCode::Key6, "Key6"),
+    (KeyCode::Key7, "Key7"),
+    (KeyCode::Key8, "Key8"),
+    (KeyCode::Key9, "Key9"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Equal, "Equal"),
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::LeftBracket, "LeftBracket"),
+    (KeyCode::Backslash, "Backslash"),
+    (KeyCode::RightBracket, "RightBracket"),
+    (KeyCode::GraveAccent, "GraveAccent"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::CapsLock, "CapsLock"),
+    (KeyCode::ScrollLock, "ScrollLock"),
+    (KeyCode::NumLock, "NumLock"),
+    (KeyCode::PrintScreen, "PrintScreen"),
+    (KeyCode::Pause, "Pause"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::F13, "F13"),
+    (KeyCode::F14, "F14"),
+    (KeyCode::F15, "F15"),
+    (KeyCode::F16, "F16"),
+    (KeyCode::F17, "F17"),
+    (KeyCode::F18, "F18"),
+    (KeyCode::F19, "F19"),
+    (KeyCode::F20, "F20"),
+    (KeyCode::F21, "F21"),
+    (KeyCode::F22, "F22"),
+    (KeyCode::F23, "F23"),
+    (KeyCode::F24, "F24"),
+    (KeyCode::F25, "F25"),
+    (KeyCode::Kp0, "Kp0"),
+    (KeyCode::Kp1, "Kp1"),
+    (KeyCode::Kp2, "Kp2"),
+    (KeyCode::Kp3, "Kp3"),
+    (KeyCode::Kp4, "Kp4"),
+    (KeyCode::Kp5, "Kp5"),
+    (KeyCode::Kp6, "Kp6"),
+    (KeyCode::Kp7, "Kp7"),
+    (KeyCode::Kp8, "Kp8"),
+    (KeyCode::Kp9, "Kp9"),
+    (KeyCode::KpDecimal, "KpDecimal"),
+    (KeyCode::KpDivide, "KpDivide"),
+    (KeyCode::KpMultiply, "KpMultiply"),
+    (KeyCode::KpSubtract, "KpSubtract"),
+    (KeyCode::KpAdd, "KpAdd"),
+    (KeyCode::KpEnter, "KpEnter"),
+    (KeyCode::KpEqual, "KpEqual"),
+    (KeyCode::LeftShift, "LeftShift"),
+    (KeyCode::LeftControl, "LeftControl"),
+    (KeyCode::LeftAlt, "LeftAlt"),
+    (KeyCode::LeftSuper, "LeftSuper"),
+    (KeyCode::RightShift, "RightShift"),
+    (KeyCode::RightControl, "RightControl"),
+    (KeyCode::RightAlt, "RightAlt"),
+    (KeyCode::RightSuper, "RightSuper"),
+    (KeyCode::Menu, "Menu"),
+];
+
+/// Something the player can do, independent of the keys that trigger it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    Thrust,
+    Brake,
+    TurnLeft,
+    TurnRight,
+    FireBullet,
+    LaunchRocket,
+    SpawnDrone,
+    SpawnMine,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::Thrust,
+        Action::Brake,
+        Action::TurnLeft,
+        Action::TurnRight,
+        Action::FireBullet,
+        Action::LaunchRocket,
+        Action::SpawnDrone,
+        Action::SpawnMine,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Thrust => "Thrust",
+            Action::Brake => "Brake",
+            Action::TurnLeft => "Turn left",
+            Action::TurnRight => "Turn right",
+            Action::FireBullet => "Fire",
+            Action::LaunchRocket => "Launch rocket",
+            Action::SpawnDrone => "Spawn drone",
+            Action::SpawnMine => "Spawn mine",
+        }
+    }
+}
+
+/// A bindable key, stored in the bindings file by name, e.g. `"LeftShift"`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key(pub KeyCode);
+
+impl Key {
+    pub fn from_name(name: &str) -> Option<Self> {
+        KEYS.iter()
+            .find(|(_, key_name)| *key_name == name)
+            .map(|(code, _)| Key(*code))
+    }
+
+    /// `None` for keys that cannot be bound.
+    pub fn name(&self) -> Option<&'static str> {
+        KEYS.iter()
+            .find(|(code, _)| *code == self.0)
+            .map(|(_, name)| *name)
+    }
+}
+
+impl Serialize for Key {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name().unwrap_or("Unknown"))
+    }
+}
+
+impl<'de> Deserialize<'de> for Key {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Key::from_name(&name).ok_or_else(|| de::Error::custom(format!("unknown key `{}`", name)))
+    }
+}
+
+/// Which keys trigger which action. Every action may have several keys, and
+/// a key belongs to at most one action.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Bindings {
+    keys: BTreeMap<Action, Vec<Key>>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        let defaults = [
+            (Action::Thrust, KeyCode::W),
+            (Action::Brake, KeyCode::S),
+            (Action::TurnLeft, KeyCode::A),
+            (Action::TurnRight, KeyCode::D),
+            (Action::FireBullet, KeyCode::M),
+            (Action::LaunchRocket, KeyCode::N),
+            (Action::SpawnDrone, KeyCode::B),
+            (Action::SpawnMine, KeyCode::V),
+        ];
+        Self {
+            keys: defaults
+                .into_iter()
+                .map(|(action, code)| (action, vec![Key(code)]))
+                .collect(),
+        }
+    }
+}
+
+impl Bindings {
+    /// Where the bindings live, e.g. `~/.config/paper_space/bindings.ron`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("paper_space").join("bindings.ron"))
+    }
+
+    /// Reads the bindings, treating a missing file as the default bindings.
+    /// A key bound to more than one action is rejected.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+        let bindings: Self = ron::from_str(&text)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let mut bound: Vec<(Key, Action)> = Vec::new();
+        for (action, keys) in &bindings.keys {
+            for key in keys {
+                if let Some((_, other)) = bound.iter().find(|(bound, _)| bound == key) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "key `{}` is bound to both {} and {}",
+                            key.name().unwrap_or("Unknown"),
+                            other.name(),
+                            action.name()
+                        ),
+                    ));
+                }
+                bound.push((*key, *action));
+            }
+        }
+        Ok(bindings)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Bound keys for display, e.g. `"W/Up"`.
+    pub fn label(&self, action: Action) -> String {
+        let names: Vec<&str> = self
+            .keys(action)
+            .iter()
+            .filter_map(|key| key.name())
+            .collect();
+        if names.is_empty() {
+            "Unbound".to_string()
+        } else {
+            names.join("/")
+        }
+    }
+
+    /// Makes `key` trigger `action`, taking it away from any other action.
+    /// With `replace` the action loses its other keys. Keys that cannot be
+    /// bound are ignored.
+    pub fn bind(&mut self, action: Action, key: Key, replace: bool) {
+        if key.name().is_none() {
+            return;
+        }
+        self.keys
+            .values_mut()
+            .for_each(|keys| keys.retain(|bound| *bound != key));
+        let keys = self.keys.entry(action).or_default();
+        if replace {
+            keys.clear();
+        }
+        keys.push(key);
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        self.keys(action).iter().any(|key| is_key_down(key.0))
+    }
+
+    pub fn read_input(&self) -> PlayerInput {
+        PlayerInput {
+            thrust: self.is_down(Action::Thrust),
+            brake: self.is_down(Action::Brake),
+            turn_left: self.is_down(Action::TurnLeft),
+            turn_right: self.is_down(Action::TurnRight),
+            fire_bullet: self.is_down(Action::FireBullet),
+            launch_rocket: self.is_down(Action::LaunchRocket),
+            spawn_drone: self.is_down(Action::SpawnDrone),
+            spawn_mine: self.is_down(Action::SpawnMine),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_text(name: &str, text: &str) -> io::Result<Bindings> {
+        let path =
+            std::env::temp_dir().join(format!("paper_space_{}_{}.ron", name, std::process::id()));
+        fs::write(&path, text).unwrap();
+        let bindings = Bindings::load(&path);
+        fs::remove_file(&path).unwrap();
+        bindings
+    }
+
+    #[test]
+    fn reserved_keys_cannot_be_bound() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Thrust, Key(KeyCode::F5), true);
+        assert_eq!(bindings.keys(Action::Thrust), &[Key(KeyCode::W)]);
+        assert!(load_text("bindings_reserved", "(keys: {Thrust: [\"Space\"]})").is_err());
+    }
+
+    #[test]
+    fn rejects_key_bound_twice() {
+        let text = "(keys: {Thrust: [\"W\"], Brake: [\"W\"]})";
+        let error = load_text("bindings_twice", text).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(load_text("bindings_once", "(keys: {Thrust: [\"W\", \"Up\"]})").is_ok());
+    }
+}
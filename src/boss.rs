@@ -0,0 +1,383 @@
+use std::f32::consts::PI;
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bullet::Bullet,
+    entity::{EntityId, EntityIds},
+    math::{fan, lerp_angle},
+    rng::Rng,
+    rockets::Target,
+    tuning::BossTuning,
+};
+
+const COLOR: Color = MAROON;
+const WRECK_COLOR: Color = DARKGRAY;
+const HULL_RADIUS: f32 = 110.0;
+const HULL_SIDES: u8 = 8;
+const SHIELD_MARGIN: f32 = 10.0;
+const BARREL_LENGTH: f32 = 26.0;
+/// Distance over which the boss eases from approaching to backing off.
+const APPROACH_DISTANCE: f32 = 200.0;
+const DEATH_BLASTS: u32 = 8;
+const DEATH_BLAST_INTERVAL: f32 = 0.3;
+
+/// Destructible section of the boss, with its own hit points and hitbox.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BossPartKind {
+    /// Shoots at the ship.
+    Turret,
+    /// Keeps the core shielded while any of them is left.
+    ShieldGenerator,
+    /// Destroying it destroys the boss.
+    Core,
+}
+
+impl BossPartKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BossPartKind::Turret => "turret",
+            BossPartKind::ShieldGenerator => "shield generator",
+            BossPartKind::Core => "core",
+        }
+    }
+
+    fn radius(&self) -> f32 {
+        match self {
+            BossPartKind::Turret => 16.0,
+            BossPartKind::ShieldGenerator => 16.0,
+            BossPartKind::Core => 30.0,
+        }
+    }
+
+    fn hit_points(&self, tuning: &BossTuning) -> u32 {
+        match self {
+            BossPartKind::Turret => tuning.turret_hit_points,
+            BossPartKind::ShieldGenerator => tuning.shield_generator_hit_points,
+            BossPartKind::Core => tuning.core_hit_points,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BossPart {
+    id: EntityId,
+    kind: BossPartKind,
+    /// Place on the hull when the boss faces along the x axis.
+    offset: Vec2,
+    hit_points: u32,
+    max_hit_points: u32,
+    /// Absolute heading of a turret's barrel.
+    aim: f32,
+    reload_time: f32,
+}
+
+impl BossPart {
+    fn new(id: EntityId, kind: BossPartKind, offset: Vec2, tuning: &BossTuning) -> Self {
+        let hit_points = kind.hit_points(tuning);
+        Self {
+            id,
+            kind,
+            offset,
+            hit_points,
+            max_hit_points: hit_points,
+            aim: Vec2::X.angle_between(offset),
+            reload_time: tuning.phase(0).reload_time,
+        }
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    pub fn kind(&self) -> BossPartKind {
+        self.kind
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.hit_points > 0
+    }
+}
+
+/// Large ship made of several parts, switching attack phases as it loses
+/// health and breaking up in a chain of blasts when its core goes.
+#[derive(Serialize, Deserialize)]
+pub struct Boss {
+    position: Vec2,
+    previous_position: Vec2,
+    velocity: Vec2,
+    angle: f32,
+    previous_angle: f32,
+    parts: Vec<BossPart>,
+    /// Index into the tuned phases.
+    phase: usize,
+    death_blasts_left: u32,
+    death_blast_time: f32,
+}
+
+impl Boss {
+    /// Every part gets its own id from `entity_ids`, so rockets can lock
+    /// onto it.
+    pub fn new(position: Vec2, tuning: &BossTuning, entity_ids: &mut EntityIds) -> Self {
+        let mut part = |kind, x, y| BossPart::new(entity_ids.allocate(), kind, vec2(x, y), tuning);
+        Self {
+            position,
+            previous_position: position,
+            velocity: Vec2::ZERO,
+            angle: 0.0,
+            previous_angle: 0.0,
+            parts: vec![
+                part(BossPartKind::Core, 0.0, 0.0),
+                part(BossPartKind::ShieldGenerator, 0.0, -55.0),
+                part(BossPartKind::ShieldGenerator, 0.0, 55.0),
+                part(BossPartKind::Turret, 70.0, -60.0),
+                part(BossPartKind::Turret, 70.0, 60.0),
+                part(BossPartKind::Turret, -70.0, -60.0),
+                part(BossPartKind::Turret, -70.0, 60.0),
+            ],
+            phase: 0,
+            death_blasts_left: DEATH_BLASTS,
+            death_blast_time: 0.0,
+        }
+    }
+
+    /// Keeps its distance from `target`, the ship, while slowly turning so
+    /// its turrets sweep around, and moves on to the next phase once its
+    /// health drops far enough.
+    pub fn update(&mut self, target: Vec2, tuning: &BossTuning, dt: f32) {
+        self.previous_position = self.position;
+        self.previous_angle = self.angle;
+        if self.is_dying() {
+            self.velocity = Vec2::ZERO;
+            return;
+        }
+
+        let fraction = self.health() as f32 / self.max_health() as f32;
+        while self.phase + 1 < tuning.phases.len()
+            && fraction <= tuning.phases[self.phase + 1].health_fraction
+        {
+            self.phase += 1;
+        }
+        let phase = tuning.phase(self.phase);
+
+        self.angle = (self.angle + phase.rotation_speed * dt) % (PI * 2.0);
+        let offset = target - self.position;
+        let approach =
+            ((offset.length() - tuning.preferred_distance) / APPROACH_DISTANCE).clamp(-1.0, 1.0);
+        self.velocity = offset.normalize_or_zero() * approach * phase.speed;
+        self.position += self.velocity * dt;
+
+        for index in 0..self.parts.len() {
+            let position = self.part_position(index);
+            let part = &mut self.parts[index];
+            if part.kind == BossPartKind::Turret && part.is_alive() {
+                part.aim = Vec2::X.angle_between(target - position);
+                part.reload_time = (part.reload_time - dt).max(0.0);
+            }
+        }
+    }
+
+    /// Every reloaded turret within range of `target` fires a volley.
+    pub fn fire(&mut self, target: Vec2, tuning: &BossTuning, bullets: &mut Vec<Bullet>) {
+        if self.is_dying() {
+            return;
+        }
+        let phase = tuning.phase(self.phase);
+        for index in 0..self.parts.len() {
+            let position = self.part_position(index);
+            let part = &mut self.parts[index];
+            if part.kind != BossPartKind::Turret
+                || !part.is_alive()
+                || part.reload_time > 0.0
+                || position.distance(target) >= tuning.fire_distance
+            {
+                continue;
+            }
+            part.reload_time = phase.reload_time;
+            for offset in fan(phase.volley, phase.volley_spread) {
+                let angle = part.aim + offset;
+                bullets.push(Bullet::hostile(
+                    position + Vec2::from_angle(angle) * BARREL_LENGTH,
+                    angle,
+                    phase.bullet_speed,
+                    tuning.fire_distance,
+                    phase.bullet_damage,
+                ));
+            }
+        }
+    }
+
+    pub fn part_position(&self, index: usize) -> Vec2 {
+        self.position + Vec2::from_angle(self.angle).rotate(self.parts[index].offset)
+    }
+
+    fn is_part_exposed(&self, part: &BossPart) -> bool {
+        part.is_alive() && !(part.kind == BossPartKind::Core && self.is_shielded())
+    }
+
+    /// Whether any shield generator still protects the core.
+    pub fn is_shielded(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| part.kind == BossPartKind::ShieldGenerator && part.is_alive())
+    }
+
+    /// Index of the standing part a shot at `position` hits; shots that
+    /// miss every part fly over the hull.
+    pub fn part_at(&self, position: Vec2, radius: f32) -> Option<usize> {
+        if self.is_dying() {
+            return None;
+        }
+        (0..self.parts.len()).find(|index| {
+            let part = &self.parts[*index];
+            part.is_alive()
+                && self.part_position(*index).distance(position) <= part.kind.radius() + radius
+        })
+    }
+
+    /// Takes `damage` off a part unless the shield protects it; returns
+    /// whether it finished the part off.
+    pub fn damage_part(&mut self, index: usize, damage: u32) -> bool {
+        if !self.is_part_exposed(&self.parts[index]) {
+            return false;
+        }
+        let part = &mut self.parts[index];
+        part.hit_points = part.hit_points.saturating_sub(damage);
+        !part.is_alive()
+    }
+
+    pub fn part(&self, index: usize) -> &BossPart {
+        &self.parts[index]
+    }
+
+    /// Positions of the parts that can currently be damaged, for drones to
+    /// aim at.
+    pub fn targets(&self) -> impl Iterator<Item = Vec2> + '_ {
+        (0..self.parts.len())
+            .filter(|index| !self.is_dying() && self.is_part_exposed(&self.parts[*index]))
+            .map(|index| self.part_position(index))
+    }
+
+    /// Exposed parts as rocket targets; they move along with the hull.
+    pub fn rocket_targets(&self) -> impl Iterator<Item = Target> + '_ {
+        (0..self.parts.len())
+            .filter(|index| !self.is_dying() && self.is_part_exposed(&self.parts[*index]))
+            .map(|index| Target {
+                id: self.parts[index].id,
+                position: self.part_position(index),
+                velocity: self.velocity,
+                radius: self.parts[index].kind.radius(),
+            })
+    }
+
+    pub fn part_ids(&self) -> impl Iterator<Item = EntityId> + '_ {
+        self.parts.iter().map(|part| part.id)
+    }
+
+    /// Next blast of the death sequence once it is due, at a random spot
+    /// on the hull.
+    pub fn death_blast(&mut self, dt: f32, rng: &mut Rng) -> Option<Vec2> {
+        if !self.is_dying() || self.death_blasts_left == 0 {
+            return None;
+        }
+        self.death_blast_time -= dt;
+        if self.death_blast_time > 0.0 {
+            return None;
+        }
+        self.death_blast_time = DEATH_BLAST_INTERVAL;
+        self.death_blasts_left -= 1;
+        let offset =
+            Vec2::from_angle(rng.gen_range(0.0, PI * 2.0)) * rng.gen_range(0.0, HULL_RADIUS);
+        Some(self.position + offset)
+    }
+
+    /// Whether the core is gone and the boss is breaking up.
+    pub fn is_dying(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| part.kind == BossPartKind::Core && !part.is_alive())
+    }
+
+    /// Whether the death sequence has played out and the wreck can go.
+    pub fn is_finished(&self) -> bool {
+        self.is_dying() && self.death_blasts_left == 0
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn radius(&self) -> f32 {
+        HULL_RADIUS
+    }
+
+    /// Number of the current attack phase, starting at 1.
+    pub fn phase(&self) -> u32 {
+        self.phase as u32 + 1
+    }
+
+    pub fn health(&self) -> u32 {
+        self.parts.iter().map(|part| part.hit_points).sum()
+    }
+
+    pub fn max_health(&self) -> u32 {
+        self.parts.iter().map(|part| part.max_hit_points).sum()
+    }
+
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+        let angle = lerp_angle(self.previous_angle, self.angle, alpha);
+        draw_poly_lines(
+            position.x,
+            position.y,
+            HULL_SIDES,
+            HULL_RADIUS,
+            angle.to_degrees(),
+            3.0,
+            COLOR,
+        );
+        let rotation = Vec2::from_angle(angle);
+        for part in &self.parts {
+            let center = position + rotation.rotate(part.offset);
+            let radius = part.kind.radius();
+            if !part.is_alive() {
+                let corner = Vec2::splat(radius * 0.7);
+                let other = vec2(corner.x, -corner.y);
+                for (start, end) in [
+                    (center - corner, center + corner),
+                    (center - other, center + other),
+                ] {
+                    draw_line(start.x, start.y, end.x, end.y, 2.0, WRECK_COLOR);
+                }
+                continue;
+            }
+            match part.kind {
+                BossPartKind::Turret => {
+                    draw_circle_lines(center.x, center.y, radius, 2.0, COLOR);
+                    let muzzle = center + Vec2::from_angle(part.aim) * BARREL_LENGTH;
+                    draw_line(center.x, center.y, muzzle.x, muzzle.y, 3.0, COLOR);
+                }
+                BossPartKind::ShieldGenerator => {
+                    draw_poly_lines(
+                        center.x,
+                        center.y,
+                        4,
+                        radius,
+                        angle.to_degrees() + 45.0,
+                        2.0,
+                        COLOR,
+                    );
+                    draw_circle(center.x, center.y, radius / 3.0, SKYBLUE);
+                }
+                BossPartKind::Core => {
+                    draw_circle_lines(center.x, center.y, radius, 3.0, COLOR);
+                    draw_circle(center.x, center.y, radius / 2.0, COLOR);
+                    if self.is_shielded() {
+                        draw_circle_lines(center.x, center.y, radius + SHIELD_MARGIN, 2.0, SKYBLUE);
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,26 +1,61 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::events::DestroyCause;
 
 const SPEED: f32 = 1500.0;
 const RADIUS: f32 = 2.0;
 const LIFETIME: f32 = 1.0;
 
+/// Side a bullet was fired by; bullets only hurt the other side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Bullet {
     position: Vec2,
     angle: f32,
+    speed: f32,
     life: f32,
+    cause: DestroyCause,
+    faction: Faction,
+    damage: f32,
 }
 
 impl Bullet {
-    pub fn new(position: Vec2, angle: f32) -> Self {
+    /// Player bullet; `cause` tells who fired it, credited for what it
+    /// destroys.
+    pub fn new(position: Vec2, angle: f32, cause: DestroyCause) -> Self {
         Self {
             position,
             angle,
+            speed: SPEED,
             life: LIFETIME,
+            cause,
+            faction: Faction::Player,
+            damage: 0.0,
+        }
+    }
+
+    /// Enemy bullet that flies `range` before fading and deals `damage` to
+    /// the ship.
+    pub fn hostile(position: Vec2, angle: f32, speed: f32, range: f32, damage: f32) -> Self {
+        Self {
+            position,
+            angle,
+            speed,
+            life: range / speed,
+            cause: DestroyCause::Enemy,
+            faction: Faction::Enemy,
+            damage,
         }
     }
 
     pub fn update(&mut self, dt: f32) {
-        self.position += Vec2::from_angle(self.angle) * SPEED * dt;
+        self.position += Vec2::from_angle(self.angle) * self.speed * dt;
         self.life -= dt;
     }
 
@@ -29,13 +64,41 @@ impl Bullet {
     }
 
     pub fn draw(&self) {
-        draw_circle(self.position.x, self.position.y, RADIUS, BLACK);
+        let color = match self.faction {
+            Faction::Player => BLACK,
+            Faction::Enemy => RED,
+        };
+        draw_circle(self.position.x, self.position.y, RADIUS, color);
     }
 
     pub fn collision(&self, position: Vec2, radius: f32) -> bool {
         position.distance(self.position) <= RADIUS + radius
     }
 
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn radius(&self) -> f32 {
+        RADIUS
+    }
+
+    pub fn direction(&self) -> Vec2 {
+        Vec2::from_angle(self.angle)
+    }
+
+    pub fn cause(&self) -> DestroyCause {
+        self.cause
+    }
+
+    pub fn faction(&self) -> Faction {
+        self.faction
+    }
+
+    pub fn damage(&self) -> f32 {
+        self.damage
+    }
+
     pub fn destroy(&mut self) {
         self.life = -1.0;
     }
@@ -2,6 +2,8 @@ use std::collections::VecDeque;
 
 use macroquad::prelude::*;
 
+use crate::events::{GameEvent, GameEventListener};
+
 const TEXT_SIZE: f32 = 20.0;
 const CONSOLE_MAX_LINES: usize = 10;
 const RELOAD_BAR_SIZE: f32 = 100.0;
@@ -17,7 +19,13 @@ pub fn debug_draw_text(text: &str, index: f32) {
 }
 
 pub fn draw_info(text: &str, y: f32, reload: f32, reload_max: f32) {
-    draw_text(text, screen_width() - TEXT_SIZE / 2.0 * text.len() as f32, y * TEXT_SIZE, TEXT_SIZE, BLACK);
+    draw_text(
+        text,
+        screen_width() - TEXT_SIZE / 2.0 * text.len() as f32,
+        y * TEXT_SIZE,
+        TEXT_SIZE,
+        BLACK,
+    );
     draw_rectangle(
         screen_width() - RELOAD_BAR_SIZE / reload_max * reload,
         TEXT_SIZE * y,
@@ -27,6 +35,7 @@ pub fn draw_info(text: &str, y: f32, reload: f32, reload_max: f32) {
     );
 }
 
+#[derive(Default)]
 pub struct Console {
     lines: VecDeque<String>,
 }
@@ -48,7 +57,7 @@ impl Console {
     pub fn draw(&self) {
         self.lines.iter().enumerate().for_each(|(index, line)| {
             draw_text(
-                &line,
+                line,
                 0.0,
                 TEXT_SIZE * index as f32 + TEXT_SIZE,
                 TEXT_SIZE,
@@ -57,3 +66,64 @@ impl Console {
         });
     }
 }
+
+impl GameEventListener for Console {
+    fn on_event(&mut self, event: &GameEvent) {
+        let (text, position) = match event {
+            GameEvent::AsteroidSpawned { position } => ("Generated asteroid".to_string(), position),
+            GameEvent::AsteroidDestroyed {
+                size,
+                cause,
+                position,
+            } => (
+                format!("Asteroid of size {} destroyed by {}", size, cause.name()),
+                position,
+            ),
+            GameEvent::AsteroidDespawned { position } => {
+                ("Asteroid despawned".to_string(), position)
+            }
+            GameEvent::EnemySpawned { kind, position } => {
+                (format!("Enemy {} arrived", kind.name()), position)
+            }
+            GameEvent::EnemyDestroyed {
+                kind,
+                cause,
+                position,
+            } => (
+                format!("Enemy {} destroyed by {}", kind.name(), cause.name()),
+                position,
+            ),
+            GameEvent::BossSpawned { position } => ("Boss arrived".to_string(), position),
+            GameEvent::BossPhaseChanged { phase, position } => {
+                (format!("Boss entered phase {}", phase), position)
+            }
+            GameEvent::BossPartDestroyed {
+                kind,
+                cause,
+                position,
+            } => (
+                format!("Boss {} destroyed by {}", kind.name(), cause.name()),
+                position,
+            ),
+            GameEvent::BossDestroyed { cause, position } => {
+                (format!("Boss destroyed by {}", cause.name()), position)
+            }
+            GameEvent::RocketHit { position } => ("Rocket hit".to_string(), position),
+            GameEvent::RocketDetonated { position } => ("Rocket detonated".to_string(), position),
+            GameEvent::DroneLost { position } => ("Drone destroyed".to_string(), position),
+            GameEvent::MineDetonated { position } => ("Mine exploded".to_string(), position),
+            GameEvent::ShipDamaged { damage, position } => {
+                (format!("Ship took {:.0} damage", damage), position)
+            }
+            GameEvent::ShipHit { position } => ("Ship destroyed".to_string(), position),
+            GameEvent::PickupCollected { kind, position } => {
+                (format!("Picked up {}", kind.name()), position)
+            }
+            GameEvent::WaveStarted { number } => {
+                self.print(format!("Wave {} started.", number));
+                return;
+            }
+        };
+        self.print(format!("{} ({}, {}).", text, position.x, position.y));
+    }
+}
@@ -1,35 +1,38 @@
 use std::f32::consts::PI;
 
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::{asteroid::Asteroid, bullet::Bullet};
+use crate::{
+    asteroid::{self, Asteroid},
+    bullet::Bullet,
+    events::DestroyCause,
+    rng::Rng,
+    steering,
+    tuning::DroneTuning,
+};
 
 const HALF_SIZE: f32 = 5.0;
 const PROPELLER_RADIUS: f32 = 7.0;
 const PROPELLER_DISTANCE: f32 = 8.0;
 const PROPELLER_ROTATION_SPEED: f32 = 40.0;
 const MAX_TARGET_OFFSET_DISTANCE: f32 = 300.0;
-const MOVE_ACCELERATION: f32 = 500.0;
-const FAST_MAX_SPEED: f32 = 600.0;
-const MAX_SPEED: f32 = 120.0;
+const AVOID_SPEED: f32 = 120.0;
 const REGENERATE_TARGET_OFFESET_DISTANCE: f32 = 5.0;
 const START_FAST_MOVING_DISTANCE: f32 = 600.0;
 const STOP_FAST_MOVING_DISTANCE: f32 = 200.0;
 const ROTATION_SPEED: f32 = 10.0;
-const FIRE_DISTANCE: f32 = 500.0;
-const RELOAD_TIME: f32 = 1.5;
 const FULL_CHARGE_VALUE: f32 = 100.0;
-const UNCHARGING_SPEED: f32 = 0.5;
-const FAST_MOVE_UNCHARGING_SPEED: f32 = 2.5;
-const FIRE_CHARGE_COST: f32 = 5.0;
 
-fn generate_target_offset() -> Vec2 {
-    let angle = rand::gen_range(0.0, PI * 2.0);
+fn generate_target_offset(rng: &mut Rng) -> Vec2 {
+    let angle = rng.gen_range(0.0, PI * 2.0);
     Vec2::from_angle(angle) * MAX_TARGET_OFFSET_DISTANCE
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Drone {
     position: Vec2,
+    previous_position: Vec2,
     speed: f32,
     angle: f32,
     animation: f32,
@@ -40,25 +43,28 @@ pub struct Drone {
 }
 
 impl Drone {
-    pub fn new(position: Vec2) -> Self {
+    pub fn new(position: Vec2, tuning: &DroneTuning, rng: &mut Rng) -> Self {
         Self {
             position,
+            previous_position: position,
             animation: 0.0,
             speed: 0.0,
             angle: 0.0,
-            target_offet: generate_target_offset(),
+            target_offet: generate_target_offset(rng),
             is_fast_moving: false,
-            reload_time: RELOAD_TIME,
+            reload_time: tuning.reload_time,
             charge: FULL_CHARGE_VALUE,
         }
     }
 
-    pub fn update(&mut self, dt: f32, target: Vec2) {
+    pub fn update(&mut self, dt: f32, target: Vec2, tuning: &DroneTuning, rng: &mut Rng) {
+        self.previous_position = self.position;
+
         if self.charge > 0.0 {
             if self.is_fast_moving {
-                self.charge -= FAST_MOVE_UNCHARGING_SPEED * dt;
+                self.charge -= tuning.fast_move_uncharging_speed * dt;
             } else {
-                self.charge -= UNCHARGING_SPEED * dt;
+                self.charge -= tuning.uncharging_speed * dt;
             }
         }
 
@@ -72,15 +78,7 @@ impl Drone {
         }
         let target = target + self.target_offet;
 
-        let angle = Vec2::from_angle(self.angle).angle_between(self.position - target);
-        if angle > 0.0 {
-            self.angle -= ROTATION_SPEED * dt;
-        } else {
-            self.angle += ROTATION_SPEED * dt;
-        }
-        while self.angle > PI * 2.0 {
-            self.angle -= PI * 2.0;
-        }
+        self.angle = steering::turn_towards(self.angle, self.position, target, ROTATION_SPEED, dt);
 
         let direction = target - self.position;
 
@@ -88,29 +86,22 @@ impl Drone {
         if distance_squared
             <= REGENERATE_TARGET_OFFESET_DISTANCE * REGENERATE_TARGET_OFFESET_DISTANCE
         {
-            self.target_offet = generate_target_offset();
+            self.target_offet = generate_target_offset(rng);
         }
 
-        self.speed += MOVE_ACCELERATION * dt;
+        let max_speed = if self.is_fast_moving {
+            tuning.fast_max_speed
+        } else {
+            tuning.max_speed
+        };
+        self.speed = steering::accelerate(self.speed, tuning.move_acceleration, max_speed, dt);
 
         if self.is_fast_moving {
             if distance_squared <= STOP_FAST_MOVING_DISTANCE * STOP_FAST_MOVING_DISTANCE {
                 self.is_fast_moving = false;
             }
-            if self.speed > FAST_MAX_SPEED {
-                self.speed = FAST_MAX_SPEED;
-            } else if self.speed < 0.0 {
-                self.speed = 0.0;
-            }
-        } else {
-            if distance_squared >= START_FAST_MOVING_DISTANCE * START_FAST_MOVING_DISTANCE {
-                self.is_fast_moving = true;
-            }
-            if self.speed > MAX_SPEED {
-                self.speed = MAX_SPEED;
-            } else if self.speed < 0.0 {
-                self.speed = 0.0;
-            }
+        } else if distance_squared >= START_FAST_MOVING_DISTANCE * START_FAST_MOVING_DISTANCE {
+            self.is_fast_moving = true;
         }
 
         self.position += Vec2::from_angle(self.angle) * self.speed * dt;
@@ -139,91 +130,91 @@ impl Drone {
         );
     }
 
-    pub fn draw(&self) {
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
         let chars_count = if self.charge >= 10.0 { 3.0 } else { 2.0 };
         draw_text(
             format!("{}%", self.charge as i16).as_str(),
-            self.position.x - (chars_count * 10.0 / 2.0),
-            self.position.y - PROPELLER_DISTANCE * 2.0,
+            position.x - (chars_count * 10.0 / 2.0),
+            position.y - PROPELLER_DISTANCE * 2.0,
             20.0,
             BLACK,
         );
         draw_line(
-            self.position.x - HALF_SIZE,
-            self.position.y - HALF_SIZE,
-            self.position.x + HALF_SIZE,
-            self.position.y - HALF_SIZE,
+            position.x - HALF_SIZE,
+            position.y - HALF_SIZE,
+            position.x + HALF_SIZE,
+            position.y - HALF_SIZE,
             2.0,
             BLACK,
         );
         draw_line(
-            self.position.x + HALF_SIZE,
-            self.position.y - HALF_SIZE,
-            self.position.x + HALF_SIZE,
-            self.position.y + HALF_SIZE,
+            position.x + HALF_SIZE,
+            position.y - HALF_SIZE,
+            position.x + HALF_SIZE,
+            position.y + HALF_SIZE,
             2.0,
             BLACK,
         );
         draw_line(
-            self.position.x + HALF_SIZE,
-            self.position.y + HALF_SIZE,
-            self.position.x - HALF_SIZE,
-            self.position.y + HALF_SIZE,
+            position.x + HALF_SIZE,
+            position.y + HALF_SIZE,
+            position.x - HALF_SIZE,
+            position.y + HALF_SIZE,
             2.0,
             BLACK,
         );
         draw_line(
-            self.position.x - HALF_SIZE,
-            self.position.y + HALF_SIZE,
-            self.position.x - HALF_SIZE,
-            self.position.y - HALF_SIZE,
+            position.x - HALF_SIZE,
+            position.y + HALF_SIZE,
+            position.x - HALF_SIZE,
+            position.y - HALF_SIZE,
             2.0,
             BLACK,
         );
         self.draw_propeller(
-            self.position.x - PROPELLER_DISTANCE,
-            self.position.y - PROPELLER_DISTANCE,
+            position.x - PROPELLER_DISTANCE,
+            position.y - PROPELLER_DISTANCE,
         );
         self.draw_propeller(
-            self.position.x + PROPELLER_DISTANCE,
-            self.position.y - PROPELLER_DISTANCE,
+            position.x + PROPELLER_DISTANCE,
+            position.y - PROPELLER_DISTANCE,
         );
         self.draw_propeller(
-            self.position.x - PROPELLER_DISTANCE,
-            self.position.y + PROPELLER_DISTANCE,
+            position.x - PROPELLER_DISTANCE,
+            position.y + PROPELLER_DISTANCE,
         );
         self.draw_propeller(
-            self.position.x + PROPELLER_DISTANCE,
-            self.position.y + PROPELLER_DISTANCE,
+            position.x + PROPELLER_DISTANCE,
+            position.y + PROPELLER_DISTANCE,
         );
     }
 
-    pub fn fire(&mut self, asteroids: &Vec<Asteroid>) -> Option<Bullet> {
+    /// Shoots at the nearest of `targets` once it is within range.
+    pub fn fire(
+        &mut self,
+        targets: impl Iterator<Item = Vec2>,
+        tuning: &DroneTuning,
+    ) -> Option<Bullet> {
         if self.is_fast_moving || self.reload_time > 0.0 {
             return None;
         }
 
-        asteroids
-            .iter()
+        targets
             .min_by(|a, b| {
-                a.position()
-                    .distance(self.position)
-                    .total_cmp(&b.position().distance(self.position))
-            })
-            .map(|asteroid| {
-                (
-                    asteroid.position(),
-                    asteroid.position().distance(self.position),
-                )
+                a.distance(self.position)
+                    .total_cmp(&b.distance(self.position))
             })
+            .map(|position| (position, position.distance(self.position)))
             .and_then(|(position, distance)| {
-                if distance < FIRE_DISTANCE {
+                if distance < tuning.fire_distance {
                     let angle = Vec2::X.angle_between(position - self.position);
-                    self.reload_time = RELOAD_TIME;
-                    self.charge -= FIRE_CHARGE_COST;
+                    self.reload_time = tuning.reload_time;
+                    self.charge -= tuning.fire_charge_cost;
                     Some(Bullet::new(
                         self.position + Vec2::from_angle(angle) * PROPELLER_RADIUS,
                         angle,
+                        DestroyCause::Drone,
                     ))
                 } else {
                     None
@@ -235,26 +226,54 @@ impl Drone {
         self.charge > 0.0
     }
 
+    /// Tops the charge back up, unless the drone is already lost.
+    pub fn recharge(&mut self) {
+        if self.is_alive() {
+            self.charge = FULL_CHARGE_VALUE;
+        }
+    }
+
     pub fn position(&self) -> Vec2 {
         self.position
     }
 
+    /// Distance within which another drone pushes this one away.
+    pub fn drone_reach(&self) -> f32 {
+        PROPELLER_DISTANCE * 4.0
+    }
+
+    /// Broad-phase radius covering every asteroid this drone can touch or
+    /// dodge, whatever the asteroid's size.
+    pub fn asteroid_reach(&self) -> f32 {
+        PROPELLER_DISTANCE * 2.0 + asteroid::MAX_RADIUS * 3.0
+    }
+
+    pub fn radius(&self) -> f32 {
+        PROPELLER_DISTANCE + PROPELLER_RADIUS
+    }
+
+    /// Knocks the drone out, e.g. when enemy fire hits it.
+    pub fn destroy(&mut self) {
+        self.charge = -1.0;
+    }
+
     pub fn drone_collision(&mut self, other: Vec2, dt: f32) {
-        let direction = self.position - other;
-        if direction.length() <= PROPELLER_DISTANCE * 4.0 {
-            self.position += direction.normalize() * MAX_SPEED * dt;
-        }
+        self.position = steering::avoid(self.position, other, self.drone_reach(), AVOID_SPEED, dt);
     }
 
     pub fn asteroid_collision(&mut self, asteroid: &Asteroid, dt: f32) -> bool {
-        let direction = self.position - asteroid.position();
-        if direction.length() <= PROPELLER_DISTANCE * 2.0 + asteroid.radius() * 2.0 {
-            self.charge = -1.0;
+        let distance = self.position.distance(asteroid.position());
+        if distance <= PROPELLER_DISTANCE * 2.0 + asteroid.radius() * 2.0 {
+            self.destroy();
             return true;
         }
-        if direction.length() <= PROPELLER_DISTANCE * 2.0 + asteroid.radius() * 2.0 * 2.0 {
-            self.position += direction.normalize() * MAX_SPEED * dt;
-        }
+        self.position = steering::avoid(
+            self.position,
+            asteroid.position(),
+            PROPELLER_DISTANCE * 2.0 + asteroid.radius() * 2.0 * 2.0,
+            AVOID_SPEED,
+            dt,
+        );
         false
     }
 }
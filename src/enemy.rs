@@ -0,0 +1,278 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asteroid::{self, Asteroid},
+    bullet::Bullet,
+    entity::EntityId,
+    geometry,
+    math::lerp_angle,
+    rng::Rng,
+    steering,
+    tuning::EnemyTuning,
+};
+
+const COLOR: Color = RED;
+const AVOID_SPEED: f32 = 400.0;
+/// How far ahead of the enemy steering goals are placed.
+const STEER_DISTANCE: f32 = 100.0;
+/// Snipers stop to aim once they are this close to their preferred distance.
+const HOLD_DISTANCE: f32 = 100.0;
+
+/// Kind of hostile ship, deciding how it moves and fights.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyKind {
+    /// Circles the ship and sprays inaccurate bullets.
+    Saucer,
+    /// Fast and unarmed; rams the ship.
+    Interceptor,
+    /// Keeps its distance and fires precise shots.
+    Sniper,
+}
+
+impl EnemyKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyKind::Saucer => "saucer",
+            EnemyKind::Interceptor => "interceptor",
+            EnemyKind::Sniper => "sniper",
+        }
+    }
+
+    fn radius(&self) -> f32 {
+        match self {
+            EnemyKind::Saucer => 22.0,
+            EnemyKind::Interceptor => 14.0,
+            EnemyKind::Sniper => 18.0,
+        }
+    }
+}
+
+fn draw_outline(points: &[Vec2]) {
+    for (start, end) in points.iter().zip(points.iter().cycle().skip(1)) {
+        draw_line(start.x, start.y, end.x, end.y, 2.0, COLOR);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Enemy {
+    id: EntityId,
+    kind: EnemyKind,
+    position: Vec2,
+    previous_position: Vec2,
+    angle: f32,
+    previous_angle: f32,
+    speed: f32,
+    reload_time: f32,
+    hit_points: u32,
+    /// Which way a saucer circles the ship: 1 or -1.
+    strafe_direction: f32,
+    /// Sum of directions away from nearby asteroids, steered along next
+    /// update.
+    avoidance: Vec2,
+    is_alive: bool,
+}
+
+impl Enemy {
+    pub fn new(
+        id: EntityId,
+        kind: EnemyKind,
+        position: Vec2,
+        tuning: &EnemyTuning,
+        rng: &mut Rng,
+    ) -> Self {
+        let tuning = tuning.kind(kind);
+        Self {
+            id,
+            kind,
+            position,
+            previous_position: position,
+            angle: 0.0,
+            previous_angle: 0.0,
+            speed: 0.0,
+            reload_time: tuning.reload_time,
+            hit_points: tuning.hit_points,
+            strafe_direction: if rng.next_f32() < 0.5 { -1.0 } else { 1.0 },
+            avoidance: Vec2::ZERO,
+            is_alive: true,
+        }
+    }
+
+    /// Steers towards where this kind of enemy wants to be relative to
+    /// `target`, the ship.
+    pub fn update(&mut self, target: Vec2, tuning: &EnemyTuning, dt: f32) {
+        self.previous_position = self.position;
+        self.previous_angle = self.angle;
+        let tuning = tuning.kind(self.kind);
+        self.reload_time = (self.reload_time - dt).max(0.0);
+
+        let offset = target - self.position;
+        let distance = offset.length();
+        let towards = offset.normalize_or_zero();
+        let (goal, acceleration) = match self.kind {
+            EnemyKind::Saucer => {
+                let approach =
+                    ((distance - tuning.preferred_distance) / STEER_DISTANCE).clamp(-1.0, 1.0);
+                let direction = towards.perp() * self.strafe_direction + towards * approach;
+                (
+                    self.position + direction * STEER_DISTANCE,
+                    tuning.acceleration,
+                )
+            }
+            EnemyKind::Interceptor => (target, tuning.acceleration),
+            EnemyKind::Sniper => {
+                if (distance - tuning.preferred_distance).abs() < HOLD_DISTANCE {
+                    (target, -tuning.acceleration)
+                } else {
+                    (
+                        target - towards * tuning.preferred_distance,
+                        tuning.acceleration,
+                    )
+                }
+            }
+        };
+        let goal = goal + std::mem::take(&mut self.avoidance) * STEER_DISTANCE;
+
+        self.angle =
+            steering::turn_towards(self.angle, self.position, goal, tuning.rotation_speed, dt);
+        self.speed = steering::accelerate(self.speed, acceleration, tuning.max_speed, dt);
+        self.position += Vec2::from_angle(self.angle) * self.speed * dt;
+    }
+
+    /// Shot at `target` once reloaded and in range; enemies with no fire
+    /// distance never shoot.
+    pub fn fire(&mut self, target: Vec2, tuning: &EnemyTuning, rng: &mut Rng) -> Option<Bullet> {
+        let tuning = tuning.kind(self.kind);
+        if self.reload_time > 0.0 || self.position.distance(target) >= tuning.fire_distance {
+            return None;
+        }
+        self.reload_time = tuning.reload_time;
+        let angle = Vec2::X.angle_between(target - self.position)
+            + rng.gen_range(-tuning.aim_spread, tuning.aim_spread);
+        Some(Bullet::hostile(
+            self.position + Vec2::from_angle(angle) * self.radius(),
+            angle,
+            tuning.bullet_speed,
+            tuning.fire_distance,
+            tuning.bullet_damage,
+        ))
+    }
+
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+        let angle = lerp_angle(self.previous_angle, self.angle, alpha);
+        let radius = self.radius();
+        match self.kind {
+            EnemyKind::Saucer => {
+                let body = [
+                    vec2(-1.0, 0.0),
+                    vec2(-0.5, -0.35),
+                    vec2(0.5, -0.35),
+                    vec2(1.0, 0.0),
+                    vec2(0.5, 0.35),
+                    vec2(-0.5, 0.35),
+                ];
+                let dome = [
+                    vec2(-0.3, -0.35),
+                    vec2(-0.15, -0.65),
+                    vec2(0.15, -0.65),
+                    vec2(0.3, -0.35),
+                ];
+                let scaled = |points: &[Vec2]| -> Vec<Vec2> {
+                    points
+                        .iter()
+                        .map(|point| position + *point * radius)
+                        .collect()
+                };
+                draw_outline(&scaled(&body));
+                draw_outline(&scaled(&dome));
+            }
+            EnemyKind::Interceptor => {
+                draw_outline(&geometry::triangle(position, angle, radius * 2.0, radius));
+            }
+            EnemyKind::Sniper => {
+                draw_poly_lines(
+                    position.x,
+                    position.y,
+                    4,
+                    radius,
+                    angle.to_degrees(),
+                    2.0,
+                    COLOR,
+                );
+                let muzzle = position + Vec2::from_angle(angle) * radius * 1.8;
+                draw_line(position.x, position.y, muzzle.x, muzzle.y, 2.0, COLOR);
+            }
+        }
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+
+    pub fn kind(&self) -> EnemyKind {
+        self.kind
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        Vec2::from_angle(self.angle) * self.speed
+    }
+
+    pub fn radius(&self) -> f32 {
+        self.kind.radius()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.is_alive
+    }
+
+    /// Takes the enemy out of play; returns `false` if it was already gone.
+    pub fn destroy(&mut self) -> bool {
+        let was_alive = self.is_alive;
+        self.is_alive = false;
+        was_alive
+    }
+
+    /// Takes a bullet hit; returns whether it finished the enemy off.
+    pub fn bullet_hit(&mut self) -> bool {
+        self.hit_points = self.hit_points.saturating_sub(1);
+        self.hit_points == 0
+    }
+
+    pub fn collision(&self, position: Vec2, radius: f32) -> bool {
+        self.position.distance(position) <= self.radius() + radius
+    }
+
+    /// Broad-phase radius covering every asteroid this enemy can touch or
+    /// dodge.
+    pub fn asteroid_reach(&self) -> f32 {
+        self.radius() + asteroid::MAX_RADIUS * 3.0
+    }
+
+    /// Slides away from a nearby asteroid; returns `true` if it crashed
+    /// into it.
+    pub fn asteroid_collision(&mut self, asteroid: &Asteroid, dt: f32) -> bool {
+        if asteroid.circle_collision(self.position, self.radius()) {
+            return true;
+        }
+        // Outrun the asteroid as well, or fast ones would still catch it.
+        let away = (self.position - asteroid.position()).normalize_or_zero();
+        let closing_speed = asteroid.velocity().dot(away).max(0.0);
+        let reach = self.radius() + asteroid.radius() * 3.0;
+        if self.position.distance(asteroid.position()) <= reach {
+            self.avoidance += away;
+        }
+        self.position = steering::avoid(
+            self.position,
+            asteroid.position(),
+            reach,
+            AVOID_SPEED + closing_speed,
+            dt,
+        );
+        false
+    }
+}
@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// Names one entity for its whole life. Once the entity is gone the id
+/// never matches anything again, even after its slot is handed out anew.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntityId {
+    index: u32,
+    generation: u32,
+}
+
+/// Hands out entity ids, reusing the slots of released ones under a new
+/// generation. Generations start at 1, so the default id is never live.
+#[derive(Default, Serialize, Deserialize)]
+pub struct EntityIds {
+    generations: Vec<u32>,
+    free: Vec<u32>,
+}
+
+impl EntityIds {
+    pub fn new() -> Self {
+        Self {
+            generations: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn allocate(&mut self) -> EntityId {
+        if let Some(index) = self.free.pop() {
+            return EntityId {
+                index,
+                generation: self.generations[index as usize],
+            };
+        }
+        self.generations.push(1);
+        EntityId {
+            index: self.generations.len() as u32 - 1,
+            generation: 1,
+        }
+    }
+
+    /// Retires `id` and frees its slot; releasing a stale id does nothing.
+    pub fn release(&mut self, id: EntityId) {
+        let Some(generation) = self.generations.get_mut(id.index as usize) else {
+            return;
+        };
+        if *generation == id.generation {
+            *generation = generation.wrapping_add(1).max(1);
+            self.free.push(id.index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn released_slot_gets_new_generation() {
+        let mut ids = EntityIds::new();
+        let first = ids.allocate();
+        let other = ids.allocate();
+        ids.release(first);
+        let reused = ids.allocate();
+        assert_eq!(reused.index, first.index);
+        assert_ne!(reused, first);
+        assert_ne!(reused, other);
+    }
+
+    #[test]
+    fn releasing_stale_id_does_nothing() {
+        let mut ids = EntityIds::new();
+        let first = ids.allocate();
+        ids.release(first);
+        let reused = ids.allocate();
+        ids.release(first);
+        assert_ne!(ids.allocate().index, reused.index);
+    }
+}
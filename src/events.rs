@@ -0,0 +1,104 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{boss::BossPartKind, enemy::EnemyKind, pickup::PickupKind};
+
+/// What destroyed an asteroid, enemy or boss.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DestroyCause {
+    Bullet,
+    Rocket,
+    Drone,
+    Mine,
+    Collision,
+    /// Enemy fire.
+    Enemy,
+}
+
+impl DestroyCause {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DestroyCause::Bullet => "bullet",
+            DestroyCause::Rocket => "rocket",
+            DestroyCause::Drone => "drone",
+            DestroyCause::Mine => "mine",
+            DestroyCause::Collision => "collision",
+            DestroyCause::Enemy => "enemy fire",
+        }
+    }
+}
+
+/// Significant moments of a simulation tick, collected for anything that
+/// wants to react to gameplay without living inside the simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameEvent {
+    AsteroidSpawned {
+        position: Vec2,
+    },
+    AsteroidDestroyed {
+        size: u8,
+        cause: DestroyCause,
+        position: Vec2,
+    },
+    AsteroidDespawned {
+        position: Vec2,
+    },
+    EnemySpawned {
+        kind: EnemyKind,
+        position: Vec2,
+    },
+    EnemyDestroyed {
+        kind: EnemyKind,
+        cause: DestroyCause,
+        position: Vec2,
+    },
+    BossSpawned {
+        position: Vec2,
+    },
+    BossPhaseChanged {
+        /// Starting at 1 for the phase the boss arrives in.
+        phase: u32,
+        position: Vec2,
+    },
+    BossPartDestroyed {
+        kind: BossPartKind,
+        cause: DestroyCause,
+        position: Vec2,
+    },
+    /// The core was destroyed; the wreck breaks up over the next moments.
+    BossDestroyed {
+        cause: DestroyCause,
+        position: Vec2,
+    },
+    RocketHit {
+        position: Vec2,
+    },
+    /// A rocket went off on its proximity fuse or self-destructed.
+    RocketDetonated {
+        position: Vec2,
+    },
+    DroneLost {
+        position: Vec2,
+    },
+    MineDetonated {
+        position: Vec2,
+    },
+    ShipDamaged {
+        damage: f32,
+        position: Vec2,
+    },
+    ShipHit {
+        position: Vec2,
+    },
+    WaveStarted {
+        number: u32,
+    },
+    PickupCollected {
+        kind: PickupKind,
+        position: Vec2,
+    },
+}
+
+pub trait GameEventListener {
+    fn on_event(&mut self, event: &GameEvent);
+}
@@ -1,12 +1,14 @@
 use std::f32::consts::PI;
 
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::particle::Particle;
+use crate::{particle::Particle, rng::Rng};
 
 const PARTICLES_PER_POWER_POINT: usize = 20;
 const PARTICLE_LIFE: f32 = 0.2;
 
+#[derive(Default, Serialize, Deserialize)]
 pub struct Explosions {
     particles: Vec<Particle>,
 }
@@ -18,13 +20,11 @@ impl Explosions {
         }
     }
 
-    pub fn explode(&mut self, center: Vec2, power: u8) {
+    pub fn explode(&mut self, center: Vec2, power: u8, rng: &mut Rng) {
         for _ in 0..((power as usize) * PARTICLES_PER_POWER_POINT) {
-            self.particles.push(Particle::new(
-                center,
-                rand::gen_range(0.0, PI * 2.0),
-                PARTICLE_LIFE + rand::gen_range(0.0, 0.5),
-            ));
+            let angle = rng.gen_range(0.0, PI * 2.0);
+            let life = PARTICLE_LIFE + rng.gen_range(0.0, 0.5);
+            self.particles.push(Particle::new(center, angle, life, rng));
         }
     }
 
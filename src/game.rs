@@ -1,63 +1,78 @@
-use std::f32::consts::PI;
+use std::{io, path::Path};
 
 use macroquad::prelude::*;
 
 use crate::{
-    asteroid::Asteroid,
-    bullet::Bullet,
+    banner::Banner,
+    bindings::{Action, Bindings},
     debug::{debug_draw_text, draw_info, Console},
-    drone::Drone,
-    explosions::Explosions,
-    mine::Mine,
-    rockets::Rockets,
-    ship::Ship,
+    events::GameEventListener,
+    input::PlayerInput,
+    pickup::{PickupKind, PowerUps},
+    replay::{Playback, Replay},
+    save,
+    simulation::{Simulation, TICK_DURATION},
+    tuning::Tuning,
 };
 
 const DISPLAY_SCALE: f32 = 1000.0;
-const BULLET_RELOAD: f32 = 0.2;
-const ROCKET_RELOAD: f32 = 0.5;
-const DRONE_RELOAD: f32 = 1.0;
-const MINE_RELOAD: f32 = 0.6;
-const ASTEROID_GENERATE_RATE: f32 = 1.0;
-const DRONE_EXPLOSION_POWER: u8 = 4;
-const ASTEROID_DESPAWN_DISTANCE: f32 = 10000.0;
-const MINE_EXPLOSION_POWER: u8 = 8;
+const MAX_FRAME_TIME: f32 = 0.25;
+const STATUS_TEXT_SIZE: f32 = 30.0;
+const STATUS_TOP: f32 = 75.0;
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 12.0;
+const BAR_LABEL_SIZE: f32 = 20.0;
+
+fn draw_status(lives: u32, wave: u32) {
+    let text = format!("Lives: {}   Wave: {}", lives, wave);
+    let dimensions = measure_text(&text, None, STATUS_TEXT_SIZE as u16, 1.0);
+    draw_text(
+        &text,
+        (screen_width() - dimensions.width) / 2.0,
+        STATUS_TOP,
+        STATUS_TEXT_SIZE,
+        BLACK,
+    );
+}
+
+/// Horizontal gauge centred on the screen, filled in proportion to `value`.
+fn draw_bar(label: &str, y: f32, value: f32, max: f32) {
+    let x = (screen_width() - BAR_WIDTH) / 2.0;
+    let fill = if max > 0.0 {
+        (value / max).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    draw_rectangle_lines(x, y, BAR_WIDTH, BAR_HEIGHT, 2.0, BLACK);
+    draw_rectangle(x, y, BAR_WIDTH * fill, BAR_HEIGHT, BLACK);
+    let dimensions = measure_text(label, None, BAR_LABEL_SIZE as u16, 1.0);
+    draw_text(
+        label,
+        x - dimensions.width - BAR_HEIGHT,
+        y + BAR_HEIGHT,
+        BAR_LABEL_SIZE,
+        BLACK,
+    );
+}
 
 pub struct Game {
-    ship: Ship,
-    bullets: Vec<Bullet>,
-    bullet_reload: f32,
-    explosions: Explosions,
-    asteroids: Vec<Asteroid>,
-    new_asteroids: Vec<Asteroid>,
-    asteroid_generate_time: f32,
-    rockets: Rockets,
-    rocket_reload: f32,
-    drones: Vec<Drone>,
-    drone_reload: f32,
-    mines: Vec<Mine>,
-    mine_reload: f32,
+    simulation: Simulation,
+    recording: Option<Replay>,
+    playback: Option<Playback>,
+    accumulator: f32,
     world_camera: Camera2D,
     console: Console,
-    is_game_over: bool,
+    banner: Banner,
+    show_debug: bool,
 }
 
 impl Game {
-    pub fn new() -> Self {
+    pub fn new(seed: u64, tuning: Tuning) -> Self {
         Self {
-            ship: Ship::new(),
-            bullets: Vec::new(),
-            bullet_reload: BULLET_RELOAD,
-            explosions: Explosions::new(),
-            asteroids: Vec::new(),
-            new_asteroids: Vec::new(),
-            asteroid_generate_time: ASTEROID_GENERATE_RATE,
-            rockets: Rockets::new(),
-            rocket_reload: ROCKET_RELOAD,
-            drones: Vec::new(),
-            drone_reload: DRONE_RELOAD,
-            mines: Vec::new(),
-            mine_reload: MINE_RELOAD,
+            recording: Some(Replay::new(seed, tuning.clone())),
+            simulation: Simulation::new(seed, tuning),
+            playback: None,
+            accumulator: 0.0,
             world_camera: Camera2D {
                 rotation: 0.0,
                 zoom: vec2(
@@ -70,241 +85,312 @@ impl Game {
                 viewport: None,
             },
             console: Console::new(),
-            is_game_over: false,
+            banner: Banner::new(),
+            show_debug: true,
         }
     }
 
-    fn generate_asteroid(&mut self, dt: f32) {
-        if self.asteroid_generate_time > 0.0 {
-            self.asteroid_generate_time -= dt;
-        } else {
-            let angle = rand::gen_range(0.0, PI * 2.0);
-            let position = self.ship.position() + Vec2::from_angle(angle) * DISPLAY_SCALE * 3.0;
-            let velocity = (self.ship.position() - position).normalize();
-            self.asteroids.push(Asteroid::new(position, velocity));
-            self.console.print(format!(
-                "Generated asteroid ({}, {}).",
-                position.x, position.y
-            ));
-            self.asteroid_generate_time = ASTEROID_GENERATE_RATE;
-        }
+    /// Game driven by a recorded replay instead of the keyboard, played
+    /// with the tuning it was recorded with.
+    pub fn from_replay(replay: Replay) -> Self {
+        let mut game = Self::new(replay.seed(), replay.tuning().clone());
+        game.playback = Some(Playback::new(replay));
+        game
     }
 
-    fn update_game_objects(&mut self, dt: f32) {
-        self.rockets.update(&mut self.asteroids, &self.ship, dt);
-        self.ship.update(dt);
-        self.bullets.iter_mut().for_each(|bullet| bullet.update(dt));
-        self.asteroids
-            .iter_mut()
-            .for_each(|asteroid| asteroid.update(dt));
-        self.explosions.update(dt);
-        self.drones.iter_mut().for_each(|drone| {
-            if let Some(bullet) = drone.fire(&self.asteroids) {
-                self.bullets.push(bullet);
-            }
-        });
-        self.drones
-            .iter_mut()
-            .for_each(|drone| drone.update(dt, self.ship.position()));
-    }
-
-    fn process_collisions(&mut self, dt: f32) {
-        for bullet in &mut self.bullets {
-            for asteroid in &mut self.asteroids {
-                if bullet.collision(asteroid.position(), asteroid.radius()) {
-                    bullet.destroy();
-                    asteroid.destroy(&mut self.new_asteroids);
-                    self.explosions
-                        .explode(asteroid.position(), asteroid.size());
-                }
-            }
-        }
-
-        let count = self.asteroids.len();
-        for i in 0..count {
-            for j in 0..count {
-                if i != j {
-                    if self.asteroids[i].collision(&self.asteroids[j]) {
-                        self.asteroids[i].destroy(&mut self.new_asteroids);
-                        self.asteroids[j].destroy(&mut self.new_asteroids);
-                        self.explosions
-                            .explode(self.asteroids[i].position(), self.asteroids[i].size());
-                        self.explosions
-                            .explode(self.asteroids[j].position(), self.asteroids[j].size());
-                    }
-                }
-            }
-            if self.ship.asteroid_collision(&self.asteroids[i]) {
-                self.is_game_over = true;
-            }
-            self.rockets.asteroid_collision(
-                &mut self.asteroids[i],
-                &mut self.new_asteroids,
-                &mut self.explosions,
-            );
-            for j in 0..self.drones.len() {
-                if self.drones[j].asteroid_collision(&self.asteroids[i], dt) {
-                    self.asteroids[i].destroy(&mut self.new_asteroids);
-                    self.explosions
-                        .explode(self.asteroids[i].position(), self.asteroids[i].size());
-                    self.explosions
-                        .explode(self.drones[j].position(), DRONE_EXPLOSION_POWER);
-                }
-            }
-            if self.asteroids[i].position().distance(self.ship.position())
-                >= ASTEROID_DESPAWN_DISTANCE
-            {
-                self.asteroids[i].despawn();
-                self.console.print(format!(
-                    "Asteroid despawned ({}, {}).",
-                    self.asteroids[i].position().x,
-                    self.asteroids[i].position().y
-                ));
-            }
-            for j in 0..self.mines.len() {
-                if self.mines[j].asteroid_collision(&self.asteroids[i]) {
-                    self.asteroids[i].destroy(&mut self.new_asteroids);
-                    self.explosions
-                        .explode(self.asteroids[i].position(), self.asteroids[i].size());
-                    self.explosions
-                        .explode(self.mines[j].position(), MINE_EXPLOSION_POWER);
-                    self.mines[j].destroy();
-                    self.console.print(format!(
-                        "Mine exploded ({}, {}).",
-                        self.mines[j].position().x,
-                        self.mines[j].position().y
-                    ));
-                }
-            }
-        }
-        for i in 0..self.drones.len() {
-            for j in 0..self.drones.len() {
-                if i != j {
-                    let position = self.drones[j].position();
-                    self.drones[i].drone_collision(position, dt);
-                }
-            }
-        }
-
-        self.asteroids.reserve(self.new_asteroids.len());
-        while let Some(new_asteroid) = self.new_asteroids.pop() {
-            self.asteroids.push(new_asteroid);
-        }
-    }
-
-    fn remove_objects(&mut self) {
-        self.bullets.retain(|bullet| bullet.alive());
-        self.asteroids.retain(|asteroid| asteroid.is_alive());
-        self.drones.retain(|drone| {
-            if drone.is_alive() {
-                true
-            } else {
-                self.explosions
-                    .explode(drone.position(), DRONE_EXPLOSION_POWER);
-                self.console.print(format!(
-                    "Drone destroyed ({}, {}).",
-                    drone.position().x,
-                    drone.position().y
-                ));
-                false
-            }
-        });
-        self.mines.retain(|mine| mine.is_alive());
+    /// Fraction of a tick left in the accumulator, used to draw objects between
+    /// their previous and current simulated positions.
+    fn alpha(&self) -> f32 {
+        self.accumulator / TICK_DURATION
     }
 
     fn update_camera(&mut self) {
+        let ship = self.simulation.ship();
+        let speed = ship.velocity().length();
         self.world_camera.zoom = vec2(
-            1.0 / (DISPLAY_SCALE + self.ship.speed() * 2.0),
-            (1.0 / (DISPLAY_SCALE + self.ship.speed() * 2.0)) * (screen_width() / screen_height()),
+            1.0 / (DISPLAY_SCALE + speed * 2.0),
+            (1.0 / (DISPLAY_SCALE + speed * 2.0)) * (screen_width() / screen_height()),
         );
-        self.world_camera.target = self.ship.position();
+        self.world_camera.target = ship.interpolated_position(self.alpha());
     }
 
-    pub fn update(&mut self) {
-        let dt = get_frame_time();
-
-        self.generate_asteroid(dt);
-
-        if self.bullet_reload > 0.0 {
-            self.bullet_reload -= dt;
-        } else {
-            if is_key_down(KeyCode::M) {
-                self.bullets.push(self.ship.fire());
-                self.bullet_reload = BULLET_RELOAD;
-            }
+    /// Advances the simulation by one tick and hands its events to listeners.
+    fn tick(&mut self, input: &PlayerInput) {
+        self.simulation.update(input, TICK_DURATION);
+        for event in self.simulation.events() {
+            self.console.on_event(event);
+            self.banner.on_event(event);
         }
+    }
 
-        if self.rocket_reload > 0.0 {
-            self.rocket_reload -= dt;
-        } else {
-            if is_key_down(KeyCode::N) {
-                self.rockets.push(self.ship.launch_rocket());
-                self.rocket_reload = ROCKET_RELOAD;
+    fn update_playback(&mut self, frame_time: f32) {
+        let Some(playback) = &mut self.playback else {
+            return;
+        };
+
+        if playback.is_paused() {
+            let input = if playback.take_step() {
+                playback.next_input()
+            } else {
+                None
+            };
+            if let Some(input) = input {
+                self.tick(&input);
             }
+            return;
         }
 
-        if self.drone_reload > 0.0 {
-            self.drone_reload -= dt;
-        } else {
-            if is_key_down(KeyCode::B) {
-                self.drones.push(self.ship.spawn_drone());
-                self.drone_reload = DRONE_RELOAD;
+        self.accumulator += frame_time * playback.speed();
+        while self.accumulator >= TICK_DURATION {
+            let input = self
+                .playback
+                .as_mut()
+                .and_then(|playback| playback.next_input());
+            match input {
+                Some(input) if !self.simulation.is_game_over() => {
+                    self.tick(&input);
+                    self.accumulator -= TICK_DURATION;
+                }
+                _ => {
+                    self.accumulator = 0.0;
+                    break;
+                }
             }
         }
+    }
 
-        if self.mine_reload > 0.0 {
-            self.mine_reload -= dt;
+    pub fn update(&mut self, input: &PlayerInput) {
+        let frame_time = get_frame_time().min(MAX_FRAME_TIME);
+        if self.playback.is_some() {
+            self.update_playback(frame_time);
         } else {
-            if is_key_down(KeyCode::V) {
-                self.mines.push(self.ship.spawn_mine());
-                self.mine_reload = MINE_RELOAD;
+            self.accumulator += frame_time;
+            while self.accumulator >= TICK_DURATION && !self.simulation.is_game_over() {
+                if let Some(recording) = &mut self.recording {
+                    recording.record(*input);
+                }
+                self.tick(input);
+                self.accumulator -= TICK_DURATION;
             }
         }
-
-        self.update_game_objects(dt);
-        self.process_collisions(dt);
-        self.remove_objects();
+        self.banner.update(frame_time);
         self.update_camera();
     }
 
-    pub fn draw(&self) {
+    /// Draws the world and HUD, labelling weapons with their bound keys.
+    pub fn draw(&self, bindings: &Bindings) {
+        let simulation = &self.simulation;
+        let alpha = self.alpha();
+
         clear_background(LIGHTGRAY);
         set_camera(&self.world_camera);
-        self.explosions.draw();
-        self.ship.draw();
-        self.bullets.iter().for_each(|bullet| bullet.draw());
-        self.asteroids.iter().for_each(|asteroid| asteroid.draw());
-        self.rockets.draw();
-        self.drones.iter().for_each(|drone| drone.draw());
-        self.mines.iter().for_each(|mine| mine.draw());
+        simulation.explosions().draw();
+        simulation.ship().draw(alpha);
+        if simulation.power_ups().is_active(PickupKind::Shield) && simulation.ship().is_alive() {
+            simulation.ship().draw_shield(alpha);
+        }
+        simulation.bullets().iter().for_each(|bullet| bullet.draw());
+        simulation
+            .asteroids()
+            .iter()
+            .for_each(|asteroid| asteroid.draw(alpha));
+        simulation.rockets().draw(alpha);
+        simulation.rockets().draw_reticles(simulation.targets());
+        simulation
+            .drones()
+            .iter()
+            .for_each(|drone| drone.draw(alpha));
+        simulation
+            .enemies()
+            .iter()
+            .for_each(|enemy| enemy.draw(alpha));
+        if let Some(boss) = simulation.boss() {
+            boss.draw(alpha);
+        }
+        simulation.mines().iter().for_each(|mine| mine.draw());
+        simulation.pickups().iter().for_each(|pickup| pickup.draw());
         set_default_camera();
 
-        draw_info("M - Fire", 1.0, self.bullet_reload, BULLET_RELOAD);
-        draw_info("N - Launch rocket", 2.0, self.rocket_reload, ROCKET_RELOAD);
-        draw_info("B - Spawn drone", 3.0, self.drone_reload, DRONE_RELOAD);
-        draw_info("V - Spawn mine", 4.0, self.mine_reload, MINE_RELOAD);
-
-        debug_draw_text(format!("FPS: {}", get_fps()).as_str(), 0.0);
-        debug_draw_text(format!("Bullets: {}", self.bullets.len()).as_str(), 1.0);
-        debug_draw_text(format!("Asteroids: {}", self.asteroids.len()).as_str(), 2.0);
-        debug_draw_text(
-            format!(
-                "Particles: {}",
-                self.explosions.particles_count()
-                    + self.ship.particles_count()
-                    + self.rockets.particles_count()
-            )
-            .as_str(),
+        simulation.score().draw();
+        draw_status(simulation.lives(), simulation.wave());
+        let ship_tuning = &simulation.tuning().ship;
+        draw_bar(
+            "Hull",
+            STATUS_TOP + BAR_HEIGHT,
+            simulation.ship().hull(),
+            ship_tuning.max_hull,
+        );
+        draw_bar(
+            "Shield",
+            STATUS_TOP + BAR_HEIGHT * 3.0,
+            simulation.ship().shield(),
+            ship_tuning.max_shield,
+        );
+        if let Some(boss) = simulation.boss() {
+            draw_bar(
+                &format!("Boss phase {}", boss.phase()),
+                STATUS_TOP + BAR_HEIGHT * 5.0,
+                boss.health() as f32,
+                boss.max_health() as f32,
+            );
+        }
+        self.banner.draw();
+        let weapons = &simulation.tuning().weapons;
+        draw_info(
+            &format!("{} - Fire", bindings.label(Action::FireBullet)),
+            1.0,
+            simulation.bullet_reload(),
+            weapons.bullet_reload,
+        );
+        draw_info(
+            &format!(
+                "{} - Launch rocket ({})",
+                bindings.label(Action::LaunchRocket),
+                simulation.rocket_ammo()
+            ),
+            2.0,
+            simulation.rocket_reload(),
+            weapons.rocket_reload,
+        );
+        draw_info(
+            &format!(
+                "{} - Spawn drone ({})",
+                bindings.label(Action::SpawnDrone),
+                simulation.drone_ammo()
+            ),
             3.0,
+            simulation.drone_reload(),
+            weapons.drone_reload,
+        );
+        draw_info(
+            &format!(
+                "{} - Spawn mine ({})",
+                bindings.label(Action::SpawnMine),
+                simulation.mine_ammo()
+            ),
+            4.0,
+            simulation.mine_reload(),
+            weapons.mine_reload,
         );
-        debug_draw_text(format!("Rockets: {}", self.rockets.len()).as_str(), 4.0);
-        debug_draw_text(format!("Drones: {}", self.drones.len()).as_str(), 5.0);
-        debug_draw_text(format!("Mines: {}", self.mines.len()).as_str(), 6.0);
+        let active_power_ups = PowerUps::KINDS
+            .into_iter()
+            .filter(|kind| simulation.power_ups().is_active(*kind));
+        for (index, kind) in active_power_ups.enumerate() {
+            let remaining = simulation.power_ups().remaining(kind);
+            draw_info(
+                &format!("{} {:.1}s", kind.name(), remaining),
+                6.0 + index as f32,
+                remaining,
+                simulation.tuning().power_ups.duration(kind),
+            );
+        }
+
+        if self.show_debug {
+            debug_draw_text(format!("FPS: {}", get_fps()).as_str(), 0.0);
+            debug_draw_text(
+                format!("Bullets: {}", simulation.bullets().len()).as_str(),
+                1.0,
+            );
+            debug_draw_text(
+                format!("Asteroids: {}", simulation.asteroids().len()).as_str(),
+                2.0,
+            );
+            debug_draw_text(
+                format!("Particles: {}", simulation.particles_count()).as_str(),
+                3.0,
+            );
+            debug_draw_text(
+                format!("Rockets: {}", simulation.rockets().len()).as_str(),
+                4.0,
+            );
+            debug_draw_text(
+                format!("Drones: {}", simulation.drones().len()).as_str(),
+                5.0,
+            );
+            debug_draw_text(format!("Mines: {}", simulation.mines().len()).as_str(), 6.0);
+            debug_draw_text(format!("Seed: {}", simulation.seed()).as_str(), 7.0);
+        }
+        if let Some(playback) = &self.playback {
+            debug_draw_text(
+                format!(
+                    "Replay {}x{}: tick {}/{} (Space - Pause, 1/2/4 - Speed, Period - Step)",
+                    playback.speed(),
+                    if playback.is_paused() { ", paused" } else { "" },
+                    playback.tick(),
+                    playback.replay().len()
+                )
+                .as_str(),
+                8.0,
+            );
+        }
 
-        self.console.draw();
+        if self.show_debug {
+            self.console.draw();
+        }
     }
 
     pub fn is_game_over(&self) -> bool {
-        self.is_game_over
+        self.simulation.is_game_over()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.simulation.seed()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.simulation.score().points()
+    }
+
+    /// Everything recorded so far in a live game. A game continued from a
+    /// save file has no recording, since its seed no longer reproduces it.
+    pub fn recording(&self) -> Option<&Replay> {
+        self.recording.as_ref()
+    }
+
+    /// Applies new balance values, unless a replay is playing back with its
+    /// own; the recording stops, since it could no longer be replayed.
+    /// Returns whether the values were applied.
+    pub fn set_tuning(&mut self, tuning: Tuning) -> bool {
+        if self.playback.is_some() {
+            return false;
+        }
+        self.simulation.set_tuning(tuning);
+        self.recording = None;
+        true
+    }
+
+    /// Whether the counters and event console are drawn.
+    pub fn set_show_debug(&mut self, show_debug: bool) {
+        self.show_debug = show_debug;
+    }
+
+    pub fn print(&mut self, text: String) {
+        self.console.print(text);
+    }
+
+    pub fn save(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        save::save(&self.simulation, &path)?;
+        self.console
+            .print(format!("Game saved to {}.", path.as_ref().display()));
+        Ok(())
+    }
+
+    pub fn load(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let tuning = self.simulation.tuning().clone();
+        self.simulation = save::load(&path)?;
+        self.simulation.set_tuning(tuning);
+        self.recording = None;
+        self.accumulator = 0.0;
+        self.console
+            .print(format!("Game loaded from {}.", path.as_ref().display()));
+        Ok(())
+    }
+
+    pub fn is_playback(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    pub fn playback_mut(&mut self) -> Option<&mut Playback> {
+        self.playback.as_mut()
     }
 }
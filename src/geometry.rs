@@ -0,0 +1,124 @@
+use macroquad::prelude::*;
+
+/// Points of `outline` rotated by `angle` and moved to `position`.
+pub fn transform(outline: &[Vec2], position: Vec2, angle: f32) -> Vec<Vec2> {
+    let rotation = Vec2::from_angle(angle);
+    outline
+        .iter()
+        .map(|point| position + rotation.rotate(*point))
+        .collect()
+}
+
+/// Corners of a triangle pointing along `angle`, the shape of the ship and
+/// of rockets.
+pub fn triangle(center: Vec2, angle: f32, height: f32, base: f32) -> [Vec2; 3] {
+    let direction = Vec2::from_angle(angle);
+    let tip = direction * height / 2.0;
+    let side = direction.perp() * base / 2.0;
+    [center + tip, center - tip + side, center - tip - side]
+}
+
+/// Sides of a closed polygon, including the one back to the first point.
+fn edges(polygon: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(start, end)| (*start, *end))
+}
+
+/// Even-odd test, so it also works for concave outlines.
+pub fn contains_point(polygon: &[Vec2], point: Vec2) -> bool {
+    edges(polygon).fold(false, |inside, (start, end)| {
+        let crosses = (start.y > point.y) != (end.y > point.y)
+            && point.x < start.x + (point.y - start.y) * (end.x - start.x) / (end.y - start.y);
+        inside != crosses
+    })
+}
+
+pub fn segment_distance(start: Vec2, end: Vec2, point: Vec2) -> f32 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared == 0.0 {
+        return point.distance(start);
+    }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    point.distance(start + segment * t)
+}
+
+fn segments_intersect(a_start: Vec2, a_end: Vec2, b_start: Vec2, b_end: Vec2) -> bool {
+    let a = a_end - a_start;
+    let b = b_end - b_start;
+    let b_start_side = a.perp_dot(b_start - a_start);
+    let b_end_side = a.perp_dot(b_end - a_start);
+    if b_start_side == 0.0 && b_end_side == 0.0 {
+        // On the same line: they touch if their spans along it overlap.
+        let length_squared = a.length_squared();
+        let from = (b_start - a_start).dot(a) / length_squared;
+        let to = (b_end - a_start).dot(a) / length_squared;
+        return from.min(to) <= 1.0 && from.max(to) >= 0.0;
+    }
+    b_start_side * b_end_side <= 0.0
+        && b.perp_dot(a_start - b_start) * b.perp_dot(a_end - b_start) <= 0.0
+}
+
+pub fn polygon_circle(polygon: &[Vec2], center: Vec2, radius: f32) -> bool {
+    contains_point(polygon, center)
+        || edges(polygon).any(|(start, end)| segment_distance(start, end, center) <= radius)
+}
+
+/// Works for concave polygons: they touch if any sides cross or one lies
+/// entirely inside the other.
+pub fn polygons_intersect(a: &[Vec2], b: &[Vec2]) -> bool {
+    edges(a).any(|(a_start, a_end)| {
+        edges(b).any(|(b_start, b_end)| segments_intersect(a_start, a_end, b_start, b_end))
+    }) || a.first().is_some_and(|point| contains_point(b, *point))
+        || b.first().is_some_and(|point| contains_point(a, *point))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(center: Vec2, half: f32) -> Vec<Vec2> {
+        vec![
+            center + vec2(-half, -half),
+            center + vec2(half, -half),
+            center + vec2(half, half),
+            center + vec2(-half, half),
+        ]
+    }
+
+    #[test]
+    fn contains_point_handles_concave_outlines() {
+        // A "C" shape open to the right.
+        let shape = [
+            vec2(0.0, 0.0),
+            vec2(30.0, 0.0),
+            vec2(30.0, 10.0),
+            vec2(10.0, 10.0),
+            vec2(10.0, 20.0),
+            vec2(30.0, 20.0),
+            vec2(30.0, 30.0),
+            vec2(0.0, 30.0),
+        ];
+        assert!(contains_point(&shape, vec2(5.0, 15.0)));
+        assert!(contains_point(&shape, vec2(20.0, 5.0)));
+        assert!(!contains_point(&shape, vec2(20.0, 15.0)));
+        assert!(!contains_point(&shape, vec2(40.0, 5.0)));
+    }
+
+    #[test]
+    fn polygons_intersect_on_crossing_sides() {
+        let a = square(vec2(0.0, 0.0), 10.0);
+        assert!(polygons_intersect(&a, &square(vec2(15.0, 0.0), 10.0)));
+        assert!(!polygons_intersect(&a, &square(vec2(25.0, 0.0), 10.0)));
+    }
+
+    #[test]
+    fn polygons_intersect_when_one_contains_the_other() {
+        let outer = square(vec2(0.0, 0.0), 50.0);
+        let inner = square(vec2(5.0, 5.0), 5.0);
+        assert!(polygons_intersect(&outer, &inner));
+        assert!(polygons_intersect(&inner, &outer));
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    cmp::Reverse,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::menu::draw_centered_text;
+
+const MAX_ENTRIES: usize = 10;
+const MAX_NAME_LENGTH: usize = 12;
+const TITLE_SIZE: f32 = 40.0;
+const TEXT_SIZE: f32 = 30.0;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub points: u32,
+}
+
+/// The best runs on this machine, highest first.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Where the table lives, e.g. `~/.local/share/paper_space/highscores.ron`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("paper_space").join("highscores.ron"))
+    }
+
+    /// Reads the table, treating a missing file as an empty table.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(error) => return Err(error),
+        };
+        let mut high_scores: Self = ron::from_str(&text)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        high_scores
+            .entries
+            .sort_by_key(|entry| Reverse(entry.points));
+        high_scores.entries.truncate(MAX_ENTRIES);
+        Ok(high_scores)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+
+    /// Whether a run with these points would make it into the table.
+    pub fn qualifies(&self, points: u32) -> bool {
+        points > 0
+            && (self.entries.len() < MAX_ENTRIES
+                || self.entries.iter().any(|entry| points > entry.points))
+    }
+
+    /// Adds the run below any equal scores, dropping whatever falls off the end.
+    pub fn insert(&mut self, name: String, points: u32) {
+        let index = self
+            .entries
+            .iter()
+            .position(|entry| points > entry.points)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, HighScore { name, points });
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    pub fn draw(&self, top: f32) {
+        self.entries.iter().enumerate().for_each(|(index, entry)| {
+            draw_centered_text(
+                &format!("{:>2}. {:<12} {:>8}", index + 1, entry.name, entry.points),
+                top + TEXT_SIZE * index as f32,
+                TEXT_SIZE,
+            );
+        });
+    }
+}
+
+/// Asks for a name after a run that made it into the high-score table.
+pub struct NameEntry {
+    points: u32,
+    name: String,
+}
+
+impl NameEntry {
+    pub fn new(points: u32) -> Self {
+        Self {
+            points,
+            name: String::new(),
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    /// Reads typed characters; returns the name once Enter confirms it.
+    pub fn update(&mut self) -> Option<String> {
+        // The queue hands out the latest character first.
+        let mut typed = Vec::new();
+        while let Some(character) = get_char_pressed() {
+            typed.push(character);
+        }
+        for character in typed.into_iter().rev() {
+            if (character.is_alphanumeric() || character == ' ')
+                && self.name.chars().count() < MAX_NAME_LENGTH
+            {
+                self.name.push(character);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.name.pop();
+        }
+        let name = self.name.trim();
+        if is_key_pressed(KeyCode::Enter) && !name.is_empty() {
+            return Some(name.to_string());
+        }
+        None
+    }
+
+    pub fn draw(&self, high_scores: &HighScores) {
+        let top = screen_height() / 4.0;
+        draw_centered_text(&format!("New high score: {}", self.points), top, TITLE_SIZE);
+        draw_centered_text(
+            &format!("Name: {}_", self.name),
+            top + TITLE_SIZE * 1.5,
+            TEXT_SIZE,
+        );
+        draw_centered_text("Enter - Confirm", top + TITLE_SIZE * 2.5, TEXT_SIZE);
+        high_scores.draw(top + TITLE_SIZE * 4.0);
+    }
+}
@@ -0,0 +1,47 @@
+/// Player controls sampled for a single simulation step.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct PlayerInput {
+    pub thrust: bool,
+    pub brake: bool,
+    pub turn_left: bool,
+    pub turn_right: bool,
+    pub fire_bullet: bool,
+    pub launch_rocket: bool,
+    pub spawn_drone: bool,
+    pub spawn_mine: bool,
+}
+
+impl PlayerInput {
+    /// Packs the controls into one byte, one bit per control.
+    pub fn to_bits(&self) -> u8 {
+        [
+            self.thrust,
+            self.brake,
+            self.turn_left,
+            self.turn_right,
+            self.fire_bullet,
+            self.launch_rocket,
+            self.spawn_drone,
+            self.spawn_mine,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (index, pressed)| {
+            bits | ((*pressed as u8) << index)
+        })
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        let pressed = |index: u8| bits & (1 << index) != 0;
+        Self {
+            thrust: pressed(0),
+            brake: pressed(1),
+            turn_left: pressed(2),
+            turn_right: pressed(3),
+            fire_bullet: pressed(4),
+            launch_rocket: pressed(5),
+            spawn_drone: pressed(6),
+            spawn_mine: pressed(7),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+pub mod app;
+pub mod asteroid;
+pub mod banner;
+pub mod bindings;
+pub mod boss;
+pub mod bullet;
+pub mod debug;
+pub mod drone;
+pub mod enemy;
+pub mod entity;
+pub mod events;
+pub mod explosions;
+pub mod game;
+pub mod geometry;
+pub mod high_scores;
+pub mod input;
+pub mod math;
+pub mod menu;
+pub mod mine;
+pub mod particle;
+pub mod pickup;
+pub mod replay;
+pub mod rng;
+pub mod rocket;
+pub mod rockets;
+pub mod save;
+pub mod score;
+pub mod ship;
+pub mod simulation;
+pub mod spatial_hash;
+pub mod steering;
+pub mod tuning;
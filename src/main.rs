@@ -1,17 +1,12 @@
-use game::Game;
 use macroquad::prelude::*;
+use paper_space::{
+    app::App,
+    game::Game,
+    replay::Replay,
+    tuning::{Tuning, TuningWatcher},
+};
 
-mod asteroid;
-mod bullet;
-mod explosions;
-mod game;
-mod particle;
-mod ship;
-mod rocket;
-mod rockets;
-mod debug;
-mod drone;
-mod mine;
+const DEFAULT_TUNING_PATH: &str = "tuning.ron";
 
 fn config() -> Conf {
     Conf {
@@ -22,23 +17,53 @@ fn config() -> Conf {
     }
 }
 
+/// Value following `name` on the command line, e.g. `--seed 42`.
+fn argument(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+    }
+    None
+}
+
 #[macroquad::main(config)]
 async fn main() {
-    let mut game = Game::new();
-
-    show_mouse(false);
-
-    loop {
-        if is_key_pressed(KeyCode::Escape) {
-            break;
+    let fixed_seed = match argument("--seed").map(|value| value.parse::<u64>()) {
+        Some(Ok(seed)) => Some(seed),
+        Some(Err(error)) => {
+            eprintln!("Invalid seed: {}", error);
+            return;
+        }
+        None => None,
+    };
+    let tuning_path = argument("--tuning").unwrap_or_else(|| DEFAULT_TUNING_PATH.to_string());
+    let tuning = match Tuning::load_or_default(&tuning_path) {
+        Ok(tuning) => tuning,
+        Err(error) => {
+            eprintln!("Invalid tuning file {}: {}", tuning_path, error);
+            return;
         }
-        game.update();
-        game.draw();
+    };
+    let tuning_watcher = TuningWatcher::new(&tuning_path);
 
-        if game.is_game_over() {
-            game = Game::new();
+    let mut app = App::new(tuning.clone(), tuning_watcher, fixed_seed);
+    if let Some(path) = argument("--replay") {
+        match Replay::load(&path) {
+            Ok(replay) => app = app.with_game(Game::from_replay(replay)),
+            Err(error) => {
+                eprintln!("Failed to load replay {}: {}", path, error);
+                return;
+            }
         }
+    }
+
+    show_mouse(false);
 
+    while app.is_running() {
+        app.update();
+        app.draw();
         next_frame().await
     }
 }
@@ -0,0 +1,18 @@
+use std::f32::consts::PI;
+
+/// Interpolates between two angles along the shortest arc, so wrapping from
+/// `2π` back to `0` does not spin the shape around.
+pub fn lerp_angle(from: f32, to: f32, alpha: f32) -> f32 {
+    let mut difference = (to - from) % (PI * 2.0);
+    if difference > PI {
+        difference -= PI * 2.0;
+    } else if difference < -PI {
+        difference += PI * 2.0;
+    }
+    from + difference * alpha
+}
+
+/// Angle offsets of `count` shots spread `spacing` apart around zero.
+pub fn fan(count: u32, spacing: f32) -> impl Iterator<Item = f32> {
+    (0..count).map(move |index| (index as f32 - (count - 1) as f32 / 2.0) * spacing)
+}
@@ -0,0 +1,88 @@
+use macroquad::prelude::*;
+
+const TITLE_SIZE: f32 = 60.0;
+const ITEM_SIZE: f32 = 36.0;
+const OVERLAY_COLOR: Color = Color::new(0.78, 0.78, 0.78, 0.8);
+
+pub fn draw_centered_text(text: &str, y: f32, size: f32) {
+    let dimensions = measure_text(text, None, size as u16, 1.0);
+    draw_text(
+        text,
+        (screen_width() - dimensions.width) / 2.0,
+        y,
+        size,
+        BLACK,
+    );
+}
+
+/// Dims whatever was drawn so far, so a menu stays readable on top of it.
+pub fn draw_overlay() {
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), OVERLAY_COLOR);
+}
+
+pub fn draw_title(title: &str, y: f32) {
+    draw_centered_text(title, y, TITLE_SIZE);
+}
+
+/// Vertical list of choices driven by the arrow keys (or W/S) and Enter.
+pub struct Menu<T> {
+    items: Vec<(String, T)>,
+    selected: usize,
+}
+
+impl<T: Copy> Menu<T> {
+    pub fn new(items: Vec<(String, T)>) -> Self {
+        Self { items, selected: 0 }
+    }
+
+    /// Starts with the item holding `action` selected, e.g. to keep the
+    /// cursor in place when a menu is rebuilt with new labels.
+    pub fn with_selected(mut self, action: T) -> Self
+    where
+        T: PartialEq,
+    {
+        if let Some(index) = self.items.iter().position(|(_, item)| *item == action) {
+            self.selected = index;
+        }
+        self
+    }
+
+    pub fn selected(&self) -> Option<T> {
+        self.items.get(self.selected).map(|(_, item)| *item)
+    }
+
+    /// Moves the selection; returns the chosen item once Enter is pressed.
+    pub fn update(&mut self) -> Option<T> {
+        if self.items.is_empty() {
+            return None;
+        }
+        if is_key_pressed(KeyCode::Up) || is_key_pressed(KeyCode::W) {
+            self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+        }
+        if is_key_pressed(KeyCode::Down) || is_key_pressed(KeyCode::S) {
+            self.selected = (self.selected + 1) % self.items.len();
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            return Some(self.items[self.selected].1);
+        }
+        None
+    }
+
+    pub fn draw(&self, top: f32) {
+        self.items
+            .iter()
+            .enumerate()
+            .for_each(|(index, (label, _))| {
+                let text = if index == self.selected {
+                    format!("> {} <", label)
+                } else {
+                    label.clone()
+                };
+                draw_centered_text(&text, top + ITEM_SIZE * index as f32, ITEM_SIZE);
+            });
+    }
+
+    pub fn height(&self) -> f32 {
+        ITEM_SIZE * self.items.len() as f32
+    }
+}
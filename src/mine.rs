@@ -1,9 +1,11 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::asteroid::Asteroid;
 
 const RADIUS: f32 = 15.0;
 
+#[derive(Serialize, Deserialize)]
 pub struct Mine {
     position: Vec2,
     is_alive: bool,
@@ -33,6 +35,10 @@ impl Mine {
         self.is_alive = false;
     }
 
+    pub fn radius(&self) -> f32 {
+        RADIUS
+    }
+
     pub fn position(&self) -> Vec2 {
         self.position
     }
@@ -1,9 +1,13 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
 
 const MIN_SPEED: f32 = 400.0;
 const MAX_SPEED: f32 = 600.0;
 const RADIUS: f32 = 2.0;
 
+#[derive(Serialize, Deserialize)]
 pub struct Particle {
     position: Vec2,
     angle: f32,
@@ -12,12 +16,12 @@ pub struct Particle {
 }
 
 impl Particle {
-    pub fn new(position: Vec2, angle: f32, life: f32) -> Self {
+    pub fn new(position: Vec2, angle: f32, life: f32, rng: &mut Rng) -> Self {
         Self {
             position,
             angle,
             life,
-            speed: rand::gen_range(MIN_SPEED, MAX_SPEED),
+            speed: rng.gen_range(MIN_SPEED, MAX_SPEED),
         }
     }
 
@@ -0,0 +1,200 @@
+use std::f32::consts::PI;
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{rng::Rng, tuning::PowerUpTuning};
+
+const SIZE: f32 = 24.0;
+const SYMBOL_SIZE: f32 = 20.0;
+const ROTATION_SPEED: f32 = 1.0;
+const MIN_DRIFT_SPEED: f32 = 10.0;
+const MAX_DRIFT_SPEED: f32 = 40.0;
+const BLINK_TIME: f32 = 3.0;
+const BLINK_RATE: f32 = 6.0;
+
+/// What picking up a crate gives the ship.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PickupKind {
+    /// Refills rockets, drones and mines.
+    Supply,
+    /// Shorter bullet reload.
+    RapidFire,
+    /// Fires a fan of bullets instead of one.
+    SpreadShot,
+    /// Destroys asteroids touching the ship without damaging its shield or hull.
+    Shield,
+    /// Keeps every drone fully charged.
+    DroneRecharge,
+    /// Launches three rockets at once.
+    TripleRockets,
+}
+
+impl PickupKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PickupKind::Supply => "supply crate",
+            PickupKind::RapidFire => "rapid fire",
+            PickupKind::SpreadShot => "spread shot",
+            PickupKind::Shield => "shield",
+            PickupKind::DroneRecharge => "drone recharge",
+            PickupKind::TripleRockets => "triple rockets",
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            PickupKind::Supply => "+",
+            PickupKind::RapidFire => "R",
+            PickupKind::SpreadShot => "S",
+            PickupKind::Shield => "O",
+            PickupKind::DroneRecharge => "D",
+            PickupKind::TripleRockets => "T",
+        }
+    }
+}
+
+/// Crate left behind by a destroyed asteroid, collected by flying over it.
+#[derive(Serialize, Deserialize)]
+pub struct Pickup {
+    position: Vec2,
+    velocity: Vec2,
+    angle: f32,
+    life: f32,
+    kind: PickupKind,
+    is_alive: bool,
+}
+
+impl Pickup {
+    /// Crate drifting off in a random direction.
+    pub fn new(position: Vec2, kind: PickupKind, life: f32, rng: &mut Rng) -> Self {
+        let direction = Vec2::from_angle(rng.gen_range(0.0, PI * 2.0));
+        Self {
+            position,
+            velocity: direction * rng.gen_range(MIN_DRIFT_SPEED, MAX_DRIFT_SPEED),
+            angle: 0.0,
+            life,
+            kind,
+            is_alive: true,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.position += self.velocity * dt;
+        self.angle += ROTATION_SPEED * dt;
+        self.life -= dt;
+        if self.life <= 0.0 {
+            self.is_alive = false;
+        }
+    }
+
+    pub fn draw(&self) {
+        if self.life < BLINK_TIME && (self.life * BLINK_RATE) as u32 % 2 == 1 {
+            return;
+        }
+        draw_poly_lines(
+            self.position.x,
+            self.position.y,
+            4,
+            SIZE / 2.0,
+            self.angle.to_degrees(),
+            2.0,
+            BLACK,
+        );
+        let symbol = self.kind.symbol();
+        let dimensions = measure_text(symbol, None, SYMBOL_SIZE as u16, 1.0);
+        draw_text(
+            symbol,
+            self.position.x - dimensions.width / 2.0,
+            self.position.y + dimensions.offset_y / 2.0,
+            SYMBOL_SIZE,
+            BLACK,
+        );
+    }
+
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn radius(&self) -> f32 {
+        SIZE / 2.0
+    }
+
+    pub fn kind(&self) -> PickupKind {
+        self.kind
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.is_alive
+    }
+
+    pub fn collect(&mut self) {
+        self.is_alive = false;
+    }
+}
+
+/// Seconds left on each timed power-up; zero means inactive.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PowerUps {
+    rapid_fire: f32,
+    spread_shot: f32,
+    shield: f32,
+    drone_recharge: f32,
+    triple_rockets: f32,
+}
+
+impl PowerUps {
+    pub const KINDS: [PickupKind; 5] = [
+        PickupKind::RapidFire,
+        PickupKind::SpreadShot,
+        PickupKind::Shield,
+        PickupKind::DroneRecharge,
+        PickupKind::TripleRockets,
+    ];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn timer_mut(&mut self, kind: PickupKind) -> Option<&mut f32> {
+        match kind {
+            PickupKind::Supply => None,
+            PickupKind::RapidFire => Some(&mut self.rapid_fire),
+            PickupKind::SpreadShot => Some(&mut self.spread_shot),
+            PickupKind::Shield => Some(&mut self.shield),
+            PickupKind::DroneRecharge => Some(&mut self.drone_recharge),
+            PickupKind::TripleRockets => Some(&mut self.triple_rockets),
+        }
+    }
+
+    /// Starts the power-up, or restarts it if it is already running.
+    pub fn activate(&mut self, kind: PickupKind, tuning: &PowerUpTuning) {
+        let duration = tuning.duration(kind);
+        if let Some(timer) = self.timer_mut(kind) {
+            *timer = timer.max(duration);
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for kind in Self::KINDS {
+            if let Some(timer) = self.timer_mut(kind) {
+                *timer = (*timer - dt).max(0.0);
+            }
+        }
+    }
+
+    pub fn remaining(&self, kind: PickupKind) -> f32 {
+        match kind {
+            PickupKind::Supply => 0.0,
+            PickupKind::RapidFire => self.rapid_fire,
+            PickupKind::SpreadShot => self.spread_shot,
+            PickupKind::Shield => self.shield,
+            PickupKind::DroneRecharge => self.drone_recharge,
+            PickupKind::TripleRockets => self.triple_rockets,
+        }
+    }
+
+    pub fn is_active(&self, kind: PickupKind) -> bool {
+        self.remaining(kind) > 0.0
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{input::PlayerInput, tuning::Tuning};
+
+const MAGIC: &[u8; 4] = b"PSRP";
+const VERSION: u8 = 2;
+
+/// Seed, tuning and per-tick input of a run; replaying them reproduces the
+/// run exactly.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    seed: u64,
+    tuning: Tuning,
+    inputs: Vec<PlayerInput>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, tuning: Tuning) -> Self {
+        Self {
+            seed,
+            tuning,
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, input: PlayerInput) {
+        self.inputs.push(input);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Balance values the run was played with.
+    pub fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    pub fn input(&self, tick: usize) -> Option<PlayerInput> {
+        self.inputs.get(tick).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+
+    /// Header and length-prefixed RON tuning followed by run-length encoded
+    /// input: held keys cost three bytes per change instead of one byte per
+    /// tick.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let tuning = ron::to_string(&self.tuning).expect("tuning always serializes");
+        let mut bytes =
+            Vec::with_capacity(MAGIC.len() + 1 + 8 + 4 + tuning.len() + self.inputs.len() / 8);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&(tuning.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(tuning.as_bytes());
+
+        let mut inputs = self.inputs.iter().map(|input| input.to_bits()).peekable();
+        while let Some(bits) = inputs.next() {
+            let mut count: u16 = 1;
+            while count < u16::MAX && inputs.peek() == Some(&bits) {
+                inputs.next();
+                count += 1;
+            }
+            bytes.push(bits);
+            bytes.extend_from_slice(&count.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_string());
+
+        let (magic, rest) = bytes
+            .split_first_chunk::<4>()
+            .ok_or_else(|| invalid("replay file is too short"))?;
+        if magic != MAGIC {
+            return Err(invalid("not a replay file"));
+        }
+        let (version, rest) = rest
+            .split_first()
+            .ok_or_else(|| invalid("replay file is too short"))?;
+        if *version != VERSION {
+            return Err(invalid(&format!("unsupported replay version {}", version)));
+        }
+        let (seed, rest) = rest
+            .split_first_chunk::<8>()
+            .ok_or_else(|| invalid("replay file is too short"))?;
+        let (tuning_length, rest) = rest
+            .split_first_chunk::<4>()
+            .ok_or_else(|| invalid("replay file is too short"))?;
+        let tuning_length = u32::from_le_bytes(*tuning_length) as usize;
+        if rest.len() < tuning_length {
+            return Err(invalid("replay file is too short"));
+        }
+        let (tuning, mut rest) = rest.split_at(tuning_length);
+        let tuning =
+            std::str::from_utf8(tuning).map_err(|_| invalid("replay tuning is not text"))?;
+        let tuning: Tuning = ron::from_str(tuning)
+            .map_err(|error| invalid(&format!("replay tuning is unreadable: {}", error)))?;
+        tuning
+            .validate()
+            .map_err(|error| invalid(&format!("replay tuning is invalid: {}", error)))?;
+
+        let mut replay = Self::new(u64::from_le_bytes(*seed), tuning);
+        while let Some((run, next)) = rest.split_first_chunk::<3>() {
+            let input = PlayerInput::from_bits(run[0]);
+            let count = u16::from_le_bytes([run[1], run[2]]);
+            replay
+                .inputs
+                .extend(std::iter::repeat_n(input, count as usize));
+            rest = next;
+        }
+        if !rest.is_empty() {
+            return Err(invalid("replay file is truncated"));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+/// Feeds a recorded replay back into the game tick by tick.
+pub struct Playback {
+    replay: Replay,
+    tick: usize,
+    speed: f32,
+    is_paused: bool,
+    is_step_requested: bool,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            tick: 0,
+            speed: 1.0,
+            is_paused: false,
+            is_step_requested: false,
+        }
+    }
+
+    pub fn next_input(&mut self) -> Option<PlayerInput> {
+        let input = self.replay.input(self.tick)?;
+        self.tick += 1;
+        Some(input)
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.is_paused = !self.is_paused;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Advances a paused playback by exactly one tick.
+    pub fn step(&mut self) {
+        self.is_paused = true;
+        self.is_step_requested = true;
+    }
+
+    pub fn take_step(&mut self) -> bool {
+        std::mem::take(&mut self.is_step_requested)
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.tick >= self.replay.len()
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recorded() -> Replay {
+        let mut tuning = Tuning::default();
+        tuning.weapons.bullet_reload = 0.35;
+        let mut replay = Replay::new(1234, tuning);
+        for tick in 0..500u32 {
+            replay.record(PlayerInput::from_bits((tick / 40) as u8));
+        }
+        replay
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let replay = recorded();
+        let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        assert_eq!(loaded.seed(), replay.seed());
+        assert_eq!(loaded.inputs, replay.inputs);
+        assert_eq!(loaded.tuning().weapons.bullet_reload, 0.35);
+    }
+
+    #[test]
+    fn rejects_bad_magic() {
+        let mut bytes = recorded().to_bytes();
+        bytes[0] = b'X';
+        assert!(Replay::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_other_version() {
+        let mut bytes = recorded().to_bytes();
+        bytes[MAGIC.len()] = VERSION + 1;
+        assert!(Replay::from_bytes(&bytes).is_err());
+    }
+
+    /// Replay header carrying `tuning` as its tuning block, with no input.
+    fn with_tuning(tuning: &str) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&1234u64.to_le_bytes());
+        bytes.extend_from_slice(&(tuning.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(tuning.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn rejects_bad_tuning() {
+        assert!(Replay::from_bytes(&with_tuning("()")).is_ok());
+        for tuning in [
+            "(weapons: (",
+            "(boss: (phases: []))",
+            "(waves: (waves: []))",
+        ] {
+            let error = Replay::from_bytes(&with_tuning(tuning)).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData, "{}", tuning);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_file() {
+        let bytes = recorded().to_bytes();
+        assert!(Replay::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Replay::from_bytes(&bytes[..MAGIC.len() + 3]).is_err());
+    }
+}
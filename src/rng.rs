@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+const MULTIPLIER: u64 = 6364136223846793005;
+const INCREMENT: u64 = 1442695040888963407;
+const COSMETIC_STREAM: u64 = 0x9e3779b97f4a7c15;
+
+/// Small seedable PCG32 generator, so a run can be reproduced from its seed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// Generator for purely visual effects, derived from the gameplay seed so
+    /// particles never shift the gameplay sequence.
+    pub fn cosmetic(seed: u64) -> Self {
+        Self::new(seed ^ COSMETIC_STREAM)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old_state = self.state;
+        self.state = old_state.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+        let xorshifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        let rotation = (old_state >> 59) as u32;
+        xorshifted.rotate_right(rotation)
+    }
+
+    /// Uniform value in `[0.0, 1.0)`.
+    pub fn next_f32(&mut self) -> f32 {
+        // The top 24 bits fit the mantissa exactly, so 1.0 is never reached.
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// Uniform value in `[low, high)`.
+    pub fn gen_range<T: RandomRange>(&mut self, low: T, high: T) -> T {
+        T::gen_range(self, low, high)
+    }
+
+    /// Index into `weights`, each picked with a chance proportional to its weight.
+    pub fn pick_weighted(&mut self, weights: &[f32]) -> usize {
+        let mut roll = self.next_f32() * weights.iter().sum::<f32>();
+        for (index, weight) in weights.iter().enumerate() {
+            if roll < *weight {
+                return index;
+            }
+            roll -= weight;
+        }
+        weights
+            .iter()
+            .rposition(|weight| *weight > 0.0)
+            .unwrap_or(0)
+    }
+}
+
+pub trait RandomRange {
+    fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self;
+}
+
+impl RandomRange for f32 {
+    fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+        low + (high - low) * rng.next_f32()
+    }
+}
+
+impl RandomRange for u8 {
+    fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+        (low as f32 + (high as f32 - low as f32) * rng.next_f32()) as u8
+    }
+}
+
+impl RandomRange for usize {
+    fn gen_range(rng: &mut Rng, low: Self, high: Self) -> Self {
+        (low as f32 + (high as f32 - low as f32) * rng.next_f32()) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u32(), b.next_u32());
+        }
+    }
+
+    #[test]
+    fn ranges_exclude_upper_bound() {
+        let mut rng = Rng::new(7);
+        for _ in 0..10_000 {
+            assert!(rng.next_f32() < 1.0);
+            assert!(rng.gen_range(0usize, 3) < 3);
+        }
+    }
+}
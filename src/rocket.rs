@@ -1,90 +1,177 @@
 use std::f32::consts::PI;
 
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::{asteroid::Asteroid, particle::Particle};
+use crate::{
+    asteroid::Asteroid, entity::EntityId, geometry, math::lerp_angle, particle::Particle, rng::Rng,
+    rockets::Target, steering, tuning::RocketTuning,
+};
 
 const HEIGHT: f32 = 20.0;
 const SHOLDER: f32 = 18.0;
 const START_SPEED: f32 = 300.0;
 const MOVE_ACCELERATION: f32 = 800.0;
 const MAX_SPEED: f32 = 2000.0;
-const ROTATION_SPEED: f32 = 5.0;
 const PARTICLE_ANGLE_DIFF: f32 = PI / 5.0;
 const ACCELERATING_TIME: f32 = 0.7;
 
+/// How a rocket steers towards its target.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Guidance {
+    /// Turns at where the target is now, so it trails moving targets.
+    PurePursuit,
+    /// Turns at where the target will be when the rocket gets there.
+    LeadPursuit,
+    /// Turns to stop the line of sight to the target from rotating, which
+    /// puts the rocket on a collision course.
+    ProportionalNavigation,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Rocket {
     position: Vec2,
+    previous_position: Vec2,
     angle: f32,
+    previous_angle: f32,
     speed: f32,
     is_alive: bool,
-    accelerating_time: f32,
+    /// Seconds since launch.
+    flight_time: f32,
+    target: Option<EntityId>,
 }
 
 impl Rocket {
     pub fn new(position: Vec2, angle: f32) -> Self {
         Self {
             position,
+            previous_position: position,
             angle,
+            previous_angle: angle,
             speed: START_SPEED,
             is_alive: true,
-            accelerating_time: ACCELERATING_TIME,
+            flight_time: 0.0,
+            target: None,
         }
     }
 
-    pub fn spawn_particle(&self) -> Particle {
+    pub fn spawn_particle(&self, rng: &mut Rng) -> Particle {
         let particle_start = self.position + Vec2::from_angle(self.angle + PI) * HEIGHT / 2.0;
         let particle_angle =
-            self.angle + PI + rand::gen_range(-PARTICLE_ANGLE_DIFF, PARTICLE_ANGLE_DIFF);
-        Particle::new(
-            particle_start,
-            particle_angle,
-            0.1 + rand::gen_range(0.0, 0.1),
-        )
-    }
-
-    pub fn update(&mut self, target: Vec2, dt: f32) {
-        if self.accelerating_time > 0.0 {
-            self.accelerating_time -= dt;
-        } else {
-            let angle = Vec2::from_angle(self.angle).angle_between(self.position - target);
-            if angle > 0.0 {
-                self.angle -= ROTATION_SPEED * dt;
-            } else {
-                self.angle += ROTATION_SPEED * dt;
-            }
-            while self.angle > PI * 2.0 {
-                self.angle -= PI * 2.0;
+            self.angle + PI + rng.gen_range(-PARTICLE_ANGLE_DIFF, PARTICLE_ANGLE_DIFF);
+        let particle_life = 0.1 + rng.gen_range(0.0, 0.1);
+        Particle::new(particle_start, particle_angle, particle_life, rng)
+    }
+
+    /// Thrusts and steers at `target` until the fuel runs out, then coasts
+    /// and finally self-destructs. Returns whether the rocket detonated,
+    /// either that way or on its proximity fuse.
+    pub fn update(&mut self, target: Option<&Target>, tuning: &RocketTuning, dt: f32) -> bool {
+        self.previous_position = self.position;
+        self.previous_angle = self.angle;
+        self.flight_time += dt;
+
+        if self.has_fuel(tuning) {
+            if let Some(target) = target.filter(|_| self.flight_time > ACCELERATING_TIME) {
+                let turn_rate = self.turn_rate(target, tuning, dt);
+                self.angle = (self.angle + turn_rate * dt).rem_euclid(PI * 2.0);
             }
+            self.speed = steering::accelerate(self.speed, MOVE_ACCELERATION, MAX_SPEED, dt);
         }
+        self.position += self.velocity() * dt;
 
-        self.speed += MOVE_ACCELERATION * dt;
-
-        if self.speed > MAX_SPEED {
-            self.speed = MAX_SPEED;
-        } else if self.speed < 0.0 {
-            self.speed = 0.0;
+        let is_fused = target.is_some_and(|target| {
+            self.position.distance(target.position) <= target.radius + tuning.fuse_distance
+        });
+        let is_spent = self.flight_time > tuning.fuel + tuning.ballistic_time;
+        if is_fused || is_spent {
+            self.destroy();
         }
+        is_fused || is_spent
+    }
+
+    /// Turn the guidance law asks for, in radians per second, within what
+    /// the rocket can manage.
+    fn turn_rate(&self, target: &Target, tuning: &RocketTuning, dt: f32) -> f32 {
+        let offset = target.position - self.position;
+        let direction = self.direction();
+        let pursue = |aim: Vec2| direction.angle_between(aim - self.position) / dt;
+        // Navigation only keeps the bearing; it would never turn a rocket
+        // round towards a target behind it.
+        let guidance = match tuning.guidance {
+            Guidance::ProportionalNavigation if direction.dot(offset) < 0.0 => {
+                Guidance::PurePursuit
+            }
+            guidance => guidance,
+        };
+        let turn_rate = match guidance {
+            Guidance::PurePursuit => pursue(target.position),
+            Guidance::LeadPursuit => pursue(
+                steering::intercept(self.position, self.speed, target.position, target.velocity)
+                    .unwrap_or(target.position),
+            ),
+            Guidance::ProportionalNavigation => {
+                let closing_velocity = target.velocity - self.velocity();
+                let line_of_sight_rate =
+                    offset.perp_dot(closing_velocity) / offset.length_squared().max(1.0);
+                tuning.navigation_gain * line_of_sight_rate
+            }
+        };
+        turn_rate.clamp(-tuning.turn_rate, tuning.turn_rate)
+    }
 
-        self.position += Vec2::from_angle(self.angle) * self.speed * dt;
+    /// Whether the rocket is still under thrust rather than coasting.
+    pub fn has_fuel(&self, tuning: &RocketTuning) -> bool {
+        self.flight_time <= tuning.fuel
     }
 
-    pub fn draw(&self) {
-        let left_sholder_angle = self.angle + PI / 2.0;
-        let direction = Vec2::from_angle(self.angle) * HEIGHT / 2.0;
+    pub fn draw(&self, alpha: f32) {
+        let position = self.previous_position.lerp(self.position, alpha);
+        let angle = lerp_angle(self.previous_angle, self.angle, alpha);
+        let left_sholder_angle = angle + PI / 2.0;
+        let direction = Vec2::from_angle(angle) * HEIGHT / 2.0;
         let left_sholder_direction = Vec2::from_angle(left_sholder_angle) * SHOLDER / 2.0;
 
         draw_triangle_lines(
-            self.position + direction,
-            self.position - direction + left_sholder_direction,
-            self.position - direction - left_sholder_direction,
+            position + direction,
+            position - direction + left_sholder_direction,
+            position - direction - left_sholder_direction,
             2.0,
             BLACK,
         );
     }
 
+    pub fn position(&self) -> Vec2 {
+        self.position
+    }
+
+    pub fn collision_radius(&self) -> f32 {
+        HEIGHT
+    }
+
+    pub fn direction(&self) -> Vec2 {
+        Vec2::from_angle(self.angle)
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.direction() * self.speed
+    }
+
     pub fn asteroid_collision(&self, asteroid: &Asteroid) -> bool {
         asteroid.position().distance(self.position) <= asteroid.radius() + HEIGHT
+            && asteroid.polygon_collision(&self.triangle())
+    }
+
+    fn triangle(&self) -> [Vec2; 3] {
+        geometry::triangle(self.position, self.angle, HEIGHT, SHOLDER)
+    }
+
+    pub fn lock(&mut self, target: Option<EntityId>) {
+        self.target = target;
+    }
+
+    pub fn target(&self) -> Option<EntityId> {
+        self.target
     }
 
     pub fn is_alive(&self) -> bool {
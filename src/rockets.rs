@@ -1,7 +1,82 @@
-use crate::{rocket::Rocket, particle::Particle, asteroid::Asteroid, ship::Ship, explosions::Explosions};
+use crate::{
+    entity::EntityId,
+    particle::Particle,
+    rng::Rng,
+    rocket::Rocket,
+    tuning::{RocketTuning, WeaponTuning},
+};
 
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
+const RETICLE_COLOR: Color = ORANGE;
+const RETICLE_MARGIN: f32 = 8.0;
+const RETICLE_TICK: f32 = 10.0;
+
+/// Something rockets can lock onto.
+#[derive(Clone, Copy, Debug)]
+pub struct Target {
+    pub id: EntityId,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub radius: f32,
+}
+
+/// Everything rockets can lock onto, looked up by id.
+#[derive(Default)]
+pub struct Targets {
+    targets: Vec<Target>,
+    indices: HashMap<EntityId, usize>,
+}
+
+impl Targets {
+    /// Replaces the targets with `targets`.
+    pub fn rebuild(&mut self, targets: impl Iterator<Item = Target>) {
+        self.targets.clear();
+        self.targets.extend(targets);
+        self.indices.clear();
+        self.indices.extend(
+            self.targets
+                .iter()
+                .enumerate()
+                .map(|(index, target)| (target.id, index)),
+        );
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Target> {
+        self.indices.get(&id).map(|&index| &self.targets[index])
+    }
+
+    pub fn as_slice(&self) -> &[Target] {
+        &self.targets
+    }
+}
+
+/// Nearest of `targets` within the lock distance of `origin` and the lock
+/// angle of `direction`.
+pub fn lock_target(
+    targets: &[Target],
+    origin: Vec2,
+    direction: Vec2,
+    tuning: &WeaponTuning,
+) -> Option<EntityId> {
+    targets
+        .iter()
+        .filter(|target| {
+            let offset = target.position - origin;
+            offset.length() <= tuning.rocket_lock_distance
+                && direction.angle_between(offset).abs() <= tuning.rocket_lock_angle
+        })
+        .min_by(|a, b| {
+            a.position
+                .distance(origin)
+                .total_cmp(&b.position.distance(origin))
+        })
+        .map(|target| target.id)
+}
+
+#[derive(Default, Serialize, Deserialize)]
 pub struct Rockets {
     rockets: Vec<Rocket>,
     particles: Vec<Particle>,
@@ -15,50 +90,87 @@ impl Rockets {
         }
     }
 
-    pub fn update(&mut self, asteroids: &mut Vec<Asteroid>, ship: &Ship, dt: f32) {
+    /// Steers every rocket at its locked target; a rocket whose target is
+    /// gone locks onto the nearest one ahead of it instead. Where rockets
+    /// detonate is added to `blasts`.
+    pub fn update(
+        &mut self,
+        targets: &Targets,
+        weapons: &WeaponTuning,
+        tuning: &RocketTuning,
+        dt: f32,
+        rng: &mut Rng,
+        blasts: &mut Vec<Vec2>,
+    ) {
         self.rockets.retain(|rocket| rocket.is_alive());
-        asteroids.sort_by(|a, b| {
-            a.position()
-                .distance(ship.position())
-                .total_cmp(&b.position().distance(ship.position()))
-        });
-        for i in 0..self.rockets.len() {
-            let target = asteroids
-                .get(i)
-                .map(|asteroid| asteroid.position())
-                .unwrap_or(
-                    asteroids
-                        .last()
-                        .map(|asteroid| asteroid.position())
-                        .unwrap_or(vec2(0.0, 0.0)),
-                );
-            self.rockets[i].update(target, dt);
+        for rocket in &mut self.rockets {
+            let locked = rocket.target().and_then(|id| targets.get(id));
+            let target = match locked {
+                Some(target) => Some(target),
+                None => {
+                    let id = lock_target(
+                        targets.as_slice(),
+                        rocket.position(),
+                        rocket.direction(),
+                        weapons,
+                    );
+                    rocket.lock(id);
+                    id.and_then(|id| targets.get(id))
+                }
+            };
+            if rocket.update(target, tuning, dt) {
+                blasts.push(rocket.position());
+            }
         }
         self.particles.retain(|particle| particle.is_alive());
-        self.particles.iter_mut().for_each(|particle| particle.update(dt));
-        self.rockets.iter().for_each(|rocket| self.particles.push(rocket.spawn_particle()));
+        self.particles
+            .iter_mut()
+            .for_each(|particle| particle.update(dt));
+        self.rockets
+            .iter()
+            .filter(|rocket| rocket.has_fuel(tuning))
+            .for_each(|rocket| self.particles.push(rocket.spawn_particle(rng)));
     }
 
-    pub fn asteroid_collision(&mut self, asteroid: &mut Asteroid, new_asteroids: &mut Vec<Asteroid>, explosions: &mut Explosions) {
-        self.rockets.iter_mut().for_each(|rocket| {
-            if rocket.asteroid_collision(asteroid) {
-                asteroid.destroy(new_asteroids);
-                explosions
-                    .explode(asteroid.position(), asteroid.size());
-                rocket.destroy();
-            }
-        });
+    pub fn get(&self, index: usize) -> &Rocket {
+        &self.rockets[index]
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> &mut Rocket {
+        &mut self.rockets[index]
     }
 
     pub fn push(&mut self, rocket: Rocket) {
         self.rockets.push(rocket);
     }
 
-    pub fn draw(&self) {
-        self.rockets.iter().for_each(|rocket| rocket.draw());
+    pub fn draw(&self, alpha: f32) {
+        self.rockets.iter().for_each(|rocket| rocket.draw(alpha));
         self.particles.iter().for_each(|particle| particle.draw());
     }
 
+    /// Lock-on reticle around every target a rocket is chasing.
+    pub fn draw_reticles(&self, targets: &Targets) {
+        let mut drawn: Vec<EntityId> = Vec::new();
+        for id in self.rockets.iter().filter_map(|rocket| rocket.target()) {
+            let Some(target) = targets.get(id) else {
+                continue;
+            };
+            if drawn.contains(&id) {
+                continue;
+            }
+            drawn.push(id);
+            let center = target.position;
+            let radius = target.radius + RETICLE_MARGIN;
+            draw_circle_lines(center.x, center.y, radius, 2.0, RETICLE_COLOR);
+            for direction in [Vec2::X, Vec2::Y, -Vec2::X, -Vec2::Y] {
+                let start = center + direction * (radius - RETICLE_TICK / 2.0);
+                let end = center + direction * (radius + RETICLE_TICK);
+                draw_line(start.x, start.y, end.x, end.y, 2.0, RETICLE_COLOR);
+            }
+        }
+    }
+
     pub fn particles_count(&self) -> usize {
         self.particles.len()
     }
@@ -66,4 +178,8 @@ impl Rockets {
     pub fn len(&self) -> usize {
         self.rockets.len()
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.rockets.is_empty()
+    }
 }
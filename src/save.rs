@@ -0,0 +1,107 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::simulation::Simulation;
+
+/// Bumped whenever a change to the simulation state breaks older save files.
+const SAVE_VERSION: u32 = 16;
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Serialize)]
+struct SaveFileRef<'a> {
+    version: u32,
+    simulation: &'a Simulation,
+}
+
+#[derive(Deserialize)]
+struct SaveFile {
+    simulation: Simulation,
+}
+
+fn invalid_data(error: impl ToString) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, error.to_string())
+}
+
+/// Writes the complete world state as a versioned RON document.
+pub fn save(simulation: &Simulation, path: impl AsRef<Path>) -> io::Result<()> {
+    let save_file = SaveFileRef {
+        version: SAVE_VERSION,
+        simulation,
+    };
+    let text = ron::ser::to_string_pretty(&save_file, ron::ser::PrettyConfig::default())
+        .map_err(invalid_data)?;
+    fs::write(path, text)
+}
+
+pub fn load(path: impl AsRef<Path>) -> io::Result<Simulation> {
+    let text = fs::read_to_string(path)?;
+
+    let header: SaveHeader = ron::from_str(&text).map_err(invalid_data)?;
+    if header.version != SAVE_VERSION {
+        return Err(invalid_data(format!(
+            "save version {} is not supported, expected {}",
+            header.version, SAVE_VERSION
+        )));
+    }
+
+    let save_file: SaveFile = ron::from_str(&text).map_err(invalid_data)?;
+    Ok(save_file.simulation)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{input::PlayerInput, simulation::TICK_DURATION, tuning::Tuning};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("paper_space_{}_{}.ron", name, std::process::id()))
+    }
+
+    #[test]
+    fn round_trip_keeps_state() {
+        let mut simulation = Simulation::new(99, Tuning::default());
+        let input = PlayerInput {
+            thrust: true,
+            fire_bullet: true,
+            ..PlayerInput::default()
+        };
+        for _ in 0..300 {
+            simulation.update(&input, TICK_DURATION);
+        }
+        let path = temp_path("save_round_trip");
+        save(&simulation, &path).unwrap();
+        let loaded = load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            ron::to_string(&loaded.unwrap()).unwrap(),
+            ron::to_string(&simulation).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_other_version() {
+        let simulation = Simulation::new(99, Tuning::default());
+        let path = temp_path("save_other_version");
+        save(&simulation, &path).unwrap();
+        let text = fs::read_to_string(&path).unwrap().replacen(
+            &format!("version: {}", SAVE_VERSION),
+            &format!("version: {}", SAVE_VERSION + 1),
+            1,
+        );
+        fs::write(&path, text).unwrap();
+        let loaded = load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.err().unwrap().kind(), ErrorKind::InvalidData);
+    }
+}
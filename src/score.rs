@@ -0,0 +1,64 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{GameEvent, GameEventListener},
+    tuning::ScoreTuning,
+};
+
+const TEXT_SIZE: f32 = 40.0;
+
+/// Points collected during a run.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Score {
+    points: u32,
+    #[serde(skip)]
+    tuning: ScoreTuning,
+}
+
+impl Score {
+    pub fn new(tuning: ScoreTuning) -> Self {
+        Self { points: 0, tuning }
+    }
+
+    pub fn set_tuning(&mut self, tuning: ScoreTuning) {
+        self.tuning = tuning;
+    }
+
+    pub fn points(&self) -> u32 {
+        self.points
+    }
+
+    pub fn draw(&self) {
+        let text = format!("Score: {}", self.points);
+        let dimensions = measure_text(&text, None, TEXT_SIZE as u16, 1.0);
+        draw_text(
+            &text,
+            (screen_width() - dimensions.width) / 2.0,
+            TEXT_SIZE,
+            TEXT_SIZE,
+            BLACK,
+        );
+    }
+}
+
+impl GameEventListener for Score {
+    /// Awards points for the event, if it is worth any.
+    fn on_event(&mut self, event: &GameEvent) {
+        let tuning = &self.tuning;
+        let points = match event {
+            GameEvent::AsteroidDestroyed { size, cause, .. } => {
+                tuning.points_per_size * *size as f32 * tuning.weight(*cause)
+            }
+            GameEvent::EnemyDestroyed { kind, cause, .. } => {
+                tuning.enemy_points(*kind) * tuning.weight(*cause)
+            }
+            GameEvent::BossPartDestroyed { cause, .. } => {
+                tuning.boss_part_points * tuning.weight(*cause)
+            }
+            GameEvent::BossDestroyed { cause, .. } => tuning.boss_points * tuning.weight(*cause),
+            _ => return,
+        };
+        self.points = self.points.saturating_add(points.round() as u32);
+    }
+}
@@ -1,86 +1,135 @@
 use std::f32::consts::PI;
 
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    asteroid::Asteroid, bullet::Bullet, drone::Drone, mine::Mine, particle::Particle,
+    asteroid::Asteroid,
+    bullet::Bullet,
+    drone::Drone,
+    events::DestroyCause,
+    geometry,
+    input::PlayerInput,
+    math::lerp_angle,
+    mine::Mine,
+    particle::Particle,
+    pickup::Pickup,
+    rng::Rng,
     rocket::Rocket,
+    tuning::{DroneTuning, FlightModel, ShipTuning},
 };
 
 const HEIGHT: f32 = 25.0;
 const SHOLDER: f32 = 22.0;
-const MAX_ROTATION_SPEED: f32 = 7.0;
-const MOVE_ACCELERATION: f32 = 300.0;
-const ROTATION_ACCELERATION: f32 = 14.0;
-const MAX_SPEED: f32 = 300.0;
-const MOVE_FRICTION: f32 = 100.0;
-const ROTATION_FRICTION: f32 = 10.0;
 const PARTICLE_ANGLE_DIFF: f32 = PI / 4.0;
+const BLINK_RATE: f32 = 8.0;
+const KNOCKBACK_DAMPING: f32 = 2.0;
 
+#[derive(Serialize, Deserialize)]
 pub struct Ship {
     position: Vec2,
-    speed: f32,
+    previous_position: Vec2,
+    velocity: Vec2,
     angle: f32,
+    previous_angle: f32,
     rotation_speed: f32,
     particles: Vec<Particle>,
+    is_alive: bool,
+    invulnerable_time: f32,
+    knockback: Vec2,
+    hull: f32,
+    shield: f32,
+    shield_regen_delay: f32,
 }
 
 impl Ship {
-    pub fn new() -> Self {
+    pub fn new(tuning: &ShipTuning) -> Self {
         Self {
             position: vec2(0.0, 0.0),
-            speed: 0.0,
+            previous_position: vec2(0.0, 0.0),
+            velocity: Vec2::ZERO,
             angle: -PI / 2.0,
+            previous_angle: -PI / 2.0,
             rotation_speed: 0.0,
             particles: vec![],
+            is_alive: true,
+            invulnerable_time: 0.0,
+            knockback: Vec2::ZERO,
+            hull: tuning.max_hull,
+            shield: tuning.max_shield,
+            shield_regen_delay: 0.0,
         }
     }
 
-    fn handle_rotation_input(&mut self, dt: f32) {
-        let mut pressed = false;
-        if is_key_down(KeyCode::A) {
-            pressed = true;
-            self.rotation_speed -= ROTATION_ACCELERATION * dt;
-            if self.rotation_speed < -MAX_ROTATION_SPEED {
-                self.rotation_speed = -MAX_ROTATION_SPEED;
+    fn handle_rotation_input(&mut self, input: &PlayerInput, tuning: &ShipTuning, dt: f32) {
+        if input.turn_left {
+            self.rotation_speed -= tuning.rotation_acceleration * dt;
+            if self.rotation_speed < -tuning.max_rotation_speed {
+                self.rotation_speed = -tuning.max_rotation_speed;
             }
         }
-        if is_key_down(KeyCode::D) {
-            pressed = true;
-            self.rotation_speed += ROTATION_ACCELERATION * dt;
-            if self.rotation_speed > MAX_ROTATION_SPEED {
-                self.rotation_speed = MAX_ROTATION_SPEED;
+        if input.turn_right {
+            self.rotation_speed += tuning.rotation_acceleration * dt;
+            if self.rotation_speed > tuning.max_rotation_speed {
+                self.rotation_speed = tuning.max_rotation_speed;
             }
         }
-        if !pressed {
+        if !input.turn_left && !input.turn_right {
             if self.rotation_speed > 0.0 {
-                self.rotation_speed -= ROTATION_FRICTION * dt;
+                self.rotation_speed -= tuning.rotation_friction * dt;
             } else {
-                self.rotation_speed += ROTATION_FRICTION * dt;
+                self.rotation_speed += tuning.rotation_friction * dt;
             }
         }
     }
 
-    fn handle_move_input(&mut self, dt: f32) -> bool {
-        let acceleration;
+    fn handle_move_input(&mut self, input: &PlayerInput, tuning: &ShipTuning, dt: f32) -> bool {
+        match tuning.flight_model {
+            FlightModel::Arcade => self.handle_arcade_move_input(input, tuning, dt),
+            FlightModel::Newtonian => self.handle_newtonian_move_input(input, tuning, dt),
+        }
+    }
 
-        if is_key_down(KeyCode::W) {
-            acceleration = MOVE_ACCELERATION;
-        } else if is_key_down(KeyCode::S) {
-            acceleration = -MOVE_ACCELERATION;
+    fn handle_arcade_move_input(
+        &mut self,
+        input: &PlayerInput,
+        tuning: &ShipTuning,
+        dt: f32,
+    ) -> bool {
+        let acceleration = if input.thrust {
+            tuning.move_acceleration
+        } else if input.brake {
+            -tuning.move_acceleration
         } else {
-            acceleration = -MOVE_FRICTION;
-        }
+            -tuning.move_friction
+        };
 
-        self.speed += acceleration * dt;
+        let speed = (self.velocity.length() + acceleration * dt).clamp(0.0, tuning.max_speed);
+        self.velocity = Vec2::from_angle(self.angle) * speed;
 
-        if self.speed > MAX_SPEED {
-            self.speed = MAX_SPEED;
-        } else if self.speed < 0.0 {
-            self.speed = 0.0;
+        acceleration > 0.0
+    }
+
+    /// Thrust pushes along the facing without touching the sideways drift;
+    /// the brake fires a weaker reverse thruster.
+    fn handle_newtonian_move_input(
+        &mut self,
+        input: &PlayerInput,
+        tuning: &ShipTuning,
+        dt: f32,
+    ) -> bool {
+        let facing = Vec2::from_angle(self.angle);
+        if input.thrust {
+            self.velocity += facing * tuning.move_acceleration * dt;
+        } else if input.brake {
+            self.velocity -= facing * tuning.reverse_acceleration * dt;
+        } else if tuning.flight_assist {
+            let speed = (self.velocity.length() - tuning.move_friction * dt).max(0.0);
+            self.velocity = self.velocity.normalize_or_zero() * speed;
         }
+        self.velocity = self.velocity.clamp_length_max(tuning.max_speed);
 
-        acceleration > 0.0
+        input.thrust
     }
 
     fn apply_rotation_speed(&mut self, dt: f32) {
@@ -92,10 +141,19 @@ impl Ship {
     }
 
     fn appy_move(&mut self, dt: f32) {
-        self.position += Vec2::from_angle(self.angle) * self.speed * dt;
+        self.position += (self.velocity + self.knockback) * dt;
+        self.knockback *= (1.0 - KNOCKBACK_DAMPING * dt).max(0.0);
     }
 
-    fn update_particles(&mut self, dt: f32, accelerated: bool) {
+    fn regenerate_shield(&mut self, tuning: &ShipTuning, dt: f32) {
+        if self.shield_regen_delay > 0.0 {
+            self.shield_regen_delay -= dt;
+        } else {
+            self.shield = (self.shield + tuning.shield_regen_rate * dt).min(tuning.max_shield);
+        }
+    }
+
+    fn update_particles(&mut self, dt: f32, accelerated: bool, rng: &mut Rng) {
         self.particles.retain(|particle| particle.is_alive());
         self.particles
             .iter_mut()
@@ -104,71 +162,204 @@ impl Ship {
         if accelerated {
             let particle_start = self.position + Vec2::from_angle(self.angle + PI) * HEIGHT / 2.0;
             let particle_angle =
-                self.angle + PI + rand::gen_range(-PARTICLE_ANGLE_DIFF, PARTICLE_ANGLE_DIFF);
+                self.angle + PI + rng.gen_range(-PARTICLE_ANGLE_DIFF, PARTICLE_ANGLE_DIFF);
+            let particle_life = 0.1 + rng.gen_range(0.0, 0.1);
             self.particles.push(Particle::new(
                 particle_start,
                 particle_angle,
-                0.1 + rand::gen_range(0.0, 0.1),
+                particle_life,
+                rng,
             ));
         }
     }
 
-    pub fn update(&mut self, dt: f32) {
-        self.handle_rotation_input(dt);
+    pub fn update(&mut self, input: &PlayerInput, tuning: &ShipTuning, dt: f32, rng: &mut Rng) {
+        self.previous_position = self.position;
+        self.previous_angle = self.angle;
+        self.invulnerable_time = (self.invulnerable_time - dt).max(0.0);
+        if !self.is_alive {
+            self.update_particles(dt, false, rng);
+            return;
+        }
+        self.regenerate_shield(tuning, dt);
+        self.handle_rotation_input(input, tuning, dt);
         self.apply_rotation_speed(dt);
-        let accelerated = self.handle_move_input(dt);
+        let accelerated = self.handle_move_input(input, tuning, dt);
         self.appy_move(dt);
-        self.update_particles(dt, accelerated);
+        self.update_particles(dt, accelerated, rng);
+    }
+
+    /// Takes the ship out of play until `respawn` is called.
+    pub fn destroy(&mut self) {
+        self.is_alive = false;
+        self.velocity = Vec2::ZERO;
+        self.rotation_speed = 0.0;
+        self.knockback = Vec2::ZERO;
+    }
+
+    /// Brings the ship back where it died with full hull and shield,
+    /// ignoring hits for a while.
+    pub fn respawn(&mut self, tuning: &ShipTuning) {
+        self.is_alive = true;
+        self.invulnerable_time = tuning.invulnerability_duration;
+        self.hull = tuning.max_hull;
+        self.shield = tuning.max_shield;
+        self.shield_regen_delay = 0.0;
+    }
+
+    /// Applies an asteroid hit: the shield soaks damage before the hull, and
+    /// the ship is knocked away from the asteroid and briefly invulnerable.
+    /// Returns the damage dealt.
+    pub fn asteroid_hit(&mut self, asteroid: &Asteroid, tuning: &ShipTuning) -> f32 {
+        let relative_speed = (self.velocity() - asteroid.velocity()).length();
+        let damage = asteroid.size() as f32
+            * (tuning.damage_per_size + tuning.damage_per_speed * relative_speed);
+        self.take_damage(damage, tuning);
+
+        let away = self.away_from(asteroid.position());
+        self.position = asteroid.position() + away * (asteroid.radius() + HEIGHT);
+        self.knockback = away * tuning.knockback_speed;
+        self.velocity = Vec2::ZERO;
+        damage
+    }
+
+    /// Applies a blast centered at `center`, knocking the ship away from it.
+    pub fn explosion_hit(&mut self, center: Vec2, damage: f32, tuning: &ShipTuning) {
+        self.take_damage(damage, tuning);
+        self.knockback = self.away_from(center) * tuning.knockback_speed;
+    }
+
+    /// Takes an enemy bullet, which unlike a blast does not knock the ship back.
+    pub fn bullet_hit(&mut self, damage: f32, tuning: &ShipTuning) {
+        self.take_damage(damage, tuning);
+    }
+
+    fn take_damage(&mut self, damage: f32, tuning: &ShipTuning) {
+        let absorbed = damage.min(self.shield);
+        self.shield -= absorbed;
+        self.hull = (self.hull - (damage - absorbed)).max(0.0);
+        self.shield_regen_delay = tuning.shield_regen_delay;
+        self.invulnerable_time = self.invulnerable_time.max(tuning.hit_invulnerability);
+    }
+
+    fn away_from(&self, point: Vec2) -> Vec2 {
+        (self.position - point)
+            .try_normalize()
+            .unwrap_or(Vec2::from_angle(self.angle + PI))
     }
 
-    pub fn draw(&self) {
-        let left_sholder_angle = self.angle + PI / 2.0;
-        let direction = Vec2::from_angle(self.angle) * HEIGHT / 2.0;
+    pub fn hull(&self) -> f32 {
+        self.hull
+    }
+
+    pub fn shield(&self) -> f32 {
+        self.shield
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity + self.knockback
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.is_alive
+    }
+
+    pub fn is_invulnerable(&self) -> bool {
+        self.invulnerable_time > 0.0
+    }
+
+    /// Whether asteroids can currently hit the ship.
+    pub fn is_vulnerable(&self) -> bool {
+        self.is_alive && !self.is_invulnerable()
+    }
+
+    fn is_blinked_out(&self) -> bool {
+        self.is_invulnerable() && (self.invulnerable_time * BLINK_RATE) as u32 % 2 == 1
+    }
+
+    pub fn draw(&self, alpha: f32) {
+        let position = self.interpolated_position(alpha);
+        let angle = lerp_angle(self.previous_angle, self.angle, alpha);
+        let left_sholder_angle = angle + PI / 2.0;
+        let direction = Vec2::from_angle(angle) * HEIGHT / 2.0;
         let left_sholder_direction = Vec2::from_angle(left_sholder_angle) * SHOLDER / 2.0;
 
-        draw_triangle_lines(
-            self.position + direction,
-            self.position - direction + left_sholder_direction,
-            self.position - direction - left_sholder_direction,
-            2.0,
-            BLACK,
-        );
+        if self.is_alive && !self.is_blinked_out() {
+            draw_triangle_lines(
+                position + direction,
+                position - direction + left_sholder_direction,
+                position - direction - left_sholder_direction,
+                2.0,
+                BLACK,
+            );
+        }
 
         self.particles.iter().for_each(|particle| particle.draw());
     }
 
-    pub fn fire(&self) -> Bullet {
+    /// Bullet fired `angle_offset` radians off the ship's heading.
+    pub fn fire(&self, angle_offset: f32) -> Bullet {
         Bullet::new(
             self.position + Vec2::from_angle(self.angle) * HEIGHT / 2.0,
-            self.angle,
+            self.angle + angle_offset,
+            DestroyCause::Bullet,
         )
     }
 
-    pub fn launch_rocket(&self) -> Rocket {
+    /// Rocket launched `angle_offset` radians off the ship's heading.
+    pub fn launch_rocket(&self, angle_offset: f32) -> Rocket {
         Rocket::new(
             self.position + Vec2::from_angle(self.angle) * HEIGHT / 2.0,
-            self.angle,
+            self.angle + angle_offset,
         )
     }
 
+    pub fn draw_shield(&self, alpha: f32) {
+        let position = self.interpolated_position(alpha);
+        draw_circle_lines(position.x, position.y, HEIGHT * 1.5, 2.0, BLACK);
+    }
+
     pub fn position(&self) -> Vec2 {
         self.position
     }
 
-    pub fn speed(&self) -> f32 {
-        self.speed
+    /// Position between the last two simulation steps, for smooth rendering.
+    pub fn interpolated_position(&self, alpha: f32) -> Vec2 {
+        self.previous_position.lerp(self.position, alpha)
     }
 
     pub fn particles_count(&self) -> usize {
         self.particles.len()
     }
 
+    pub fn collision_radius(&self) -> f32 {
+        HEIGHT
+    }
+
     pub fn asteroid_collision(&self, asteroid: &Asteroid) -> bool {
         asteroid.position().distance(self.position) <= asteroid.radius() + HEIGHT
+            && asteroid.polygon_collision(&self.triangle())
     }
 
-    pub fn spawn_drone(&self) -> Drone {
-        Drone::new(self.position + Vec2::from_angle(self.angle + PI) * HEIGHT * 2.0)
+    /// Precise check against the ship's triangle, for bullets and enemies.
+    pub fn circle_collision(&self, center: Vec2, radius: f32) -> bool {
+        self.is_alive && geometry::polygon_circle(&self.triangle(), center, radius)
+    }
+
+    fn triangle(&self) -> [Vec2; 3] {
+        geometry::triangle(self.position, self.angle, HEIGHT, SHOLDER)
+    }
+
+    pub fn pickup_collision(&self, pickup: &Pickup) -> bool {
+        pickup.position().distance(self.position) <= pickup.radius() + HEIGHT
+    }
+
+    pub fn spawn_drone(&self, tuning: &DroneTuning, rng: &mut Rng) -> Drone {
+        Drone::new(
+            self.position + Vec2::from_angle(self.angle + PI) * HEIGHT * 2.0,
+            tuning,
+            rng,
+        )
     }
 
     pub fn spawn_mine(&self) -> Mine {
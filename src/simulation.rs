@@ -0,0 +1,1292 @@
+use std::f32::consts::PI;
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asteroid::{Asteroid, Material},
+    boss::{Boss, BossPartKind},
+    bullet::{Bullet, Faction},
+    drone::Drone,
+    enemy::Enemy,
+    entity::EntityIds,
+    events::{DestroyCause, GameEvent, GameEventListener},
+    explosions::Explosions,
+    input::PlayerInput,
+    math::fan,
+    mine::Mine,
+    pickup::{Pickup, PickupKind, PowerUps},
+    rng::Rng,
+    rockets::{self, Rockets, Target, Targets},
+    score::Score,
+    ship::Ship,
+    spatial_hash::SpatialHash,
+    tuning::Tuning,
+};
+
+/// Duration of one simulation step; the world always advances by this amount.
+pub const TICK_DURATION: f32 = 1.0 / 120.0;
+const DRONE_EXPLOSION_POWER: u8 = 4;
+const MINE_EXPLOSION_POWER: u8 = 8;
+const SHIP_EXPLOSION_POWER: u8 = 6;
+const EXPLOSIVE_ASTEROID_POWER: u8 = 10;
+const ENEMY_EXPLOSION_POWER: u8 = 5;
+const ROCKET_BLAST_POWER: u8 = 8;
+const BOSS_BLAST_POWER: u8 = 8;
+const BOSS_EXPLOSION_POWER: u8 = 30;
+const GRID_CELL_SIZE: f32 = 200.0;
+
+fn collision_grid() -> SpatialHash {
+    SpatialHash::new(GRID_CELL_SIZE)
+}
+
+/// Game world state and rules, independent of any window, keyboard or renderer.
+#[derive(Serialize, Deserialize)]
+pub struct Simulation {
+    #[serde(skip)]
+    tuning: Tuning,
+    seed: u64,
+    rng: Rng,
+    fx_rng: Rng,
+    ship: Ship,
+    lives: u32,
+    respawn_time: f32,
+    bullets: Vec<Bullet>,
+    bullet_reload: f32,
+    explosions: Explosions,
+    entity_ids: EntityIds,
+    asteroids: Vec<Asteroid>,
+    #[serde(skip)]
+    new_asteroids: Vec<Asteroid>,
+    /// Where rockets detonated this tick, waiting for their blasts to land.
+    #[serde(skip)]
+    rocket_blasts: Vec<Vec2>,
+    asteroid_generate_time: f32,
+    wave: u32,
+    wave_asteroids_left: u32,
+    wave_enemies_left: u32,
+    wave_break_time: f32,
+    rockets: Rockets,
+    rocket_reload: f32,
+    rocket_ammo: u32,
+    drones: Vec<Drone>,
+    drone_reload: f32,
+    drone_ammo: u32,
+    mines: Vec<Mine>,
+    mine_reload: f32,
+    mine_ammo: u32,
+    enemies: Vec<Enemy>,
+    boss: Option<Boss>,
+    pickups: Vec<Pickup>,
+    power_ups: PowerUps,
+    #[serde(skip, default = "collision_grid")]
+    grid: SpatialHash,
+    #[serde(skip)]
+    candidates: Vec<usize>,
+    /// Everything rockets can lock onto this tick.
+    #[serde(skip)]
+    targets: Targets,
+    #[serde(skip)]
+    events: Vec<GameEvent>,
+    score: Score,
+    is_game_over: bool,
+}
+
+impl Simulation {
+    pub fn new(seed: u64, tuning: Tuning) -> Self {
+        Self {
+            bullet_reload: tuning.weapons.bullet_reload,
+            asteroid_generate_time: 0.0,
+            wave: 0,
+            wave_asteroids_left: 0,
+            wave_enemies_left: 0,
+            wave_break_time: tuning.waves.break_duration,
+            rocket_reload: tuning.weapons.rocket_reload,
+            drone_reload: tuning.weapons.drone_reload,
+            mine_reload: tuning.weapons.mine_reload,
+            rocket_ammo: tuning.weapons.starting_rockets,
+            drone_ammo: tuning.weapons.starting_drones,
+            mine_ammo: tuning.weapons.starting_mines,
+            lives: tuning.ship.lives,
+            ship: Ship::new(&tuning.ship),
+            score: Score::new(tuning.score.clone()),
+            tuning,
+            seed,
+            rng: Rng::new(seed),
+            fx_rng: Rng::cosmetic(seed),
+            respawn_time: 0.0,
+            bullets: Vec::new(),
+            explosions: Explosions::new(),
+            entity_ids: EntityIds::new(),
+            asteroids: Vec::new(),
+            new_asteroids: Vec::new(),
+            rocket_blasts: Vec::new(),
+            rockets: Rockets::new(),
+            drones: Vec::new(),
+            enemies: Vec::new(),
+            boss: None,
+            mines: Vec::new(),
+            pickups: Vec::new(),
+            power_ups: PowerUps::new(),
+            grid: collision_grid(),
+            candidates: Vec::new(),
+            targets: Targets::default(),
+            events: Vec::new(),
+            is_game_over: false,
+        }
+    }
+
+    /// Spawns the asteroids of the current wave, with its enemies spread
+    /// among them, then waits out the break before starting the next one;
+    /// a boss holds the next wave back until it is destroyed.
+    fn generate_asteroid(&mut self, dt: f32) {
+        if self.wave_asteroids_left == 0 {
+            if self.boss.is_some() {
+                return;
+            }
+            self.wave_break_time -= dt;
+            if self.wave_break_time > 0.0 {
+                return;
+            }
+            self.wave += 1;
+            let wave = self.tuning.waves.wave(self.wave);
+            self.wave_asteroids_left = wave.asteroid_count;
+            self.wave_enemies_left = wave.enemy_count;
+            self.asteroid_generate_time = 0.0;
+            self.events
+                .push(GameEvent::WaveStarted { number: self.wave });
+            let every_waves = self.tuning.boss.every_waves;
+            if every_waves > 0 && self.wave.is_multiple_of(every_waves) {
+                self.spawn_boss();
+            }
+            if self.wave_asteroids_left == 0 {
+                self.wave_break_time = self.tuning.waves.break_duration;
+                return;
+            }
+        }
+
+        if self.asteroid_generate_time > 0.0 {
+            self.asteroid_generate_time -= dt;
+            return;
+        }
+        let wave = self.tuning.waves.wave(self.wave);
+        let angle = self.rng.gen_range(0.0, PI * 2.0);
+        let position = self.ship.position()
+            + Vec2::from_angle(angle) * self.tuning.asteroids.generate_distance;
+        let aim = self.rng.gen_range(-wave.aim_spread, wave.aim_spread);
+        let direction = (self.ship.position() - position)
+            .normalize()
+            .rotate(Vec2::from_angle(aim));
+        let size = self.rng.pick_weighted(&wave.size_weights) as u8 + 1;
+        let weights: Vec<f32> = wave
+            .material_weights
+            .iter()
+            .map(|(_, weight)| *weight)
+            .collect();
+        let material = wave.material_weights[self.rng.pick_weighted(&weights)].0;
+        let asteroid = Asteroid::with_size(position, direction * wave.speed, size, &mut self.rng)
+            .with_material(material, &self.tuning.asteroids);
+        self.add_asteroid(asteroid);
+        self.events.push(GameEvent::AsteroidSpawned { position });
+        self.asteroid_generate_time = wave.spawn_interval;
+        // Each remaining spawn is equally likely to bring an enemy along, so
+        // every enemy has arrived by the time the last asteroid has.
+        if self.wave_enemies_left > 0
+            && self.rng.next_f32() * (self.wave_asteroids_left as f32)
+                < self.wave_enemies_left as f32
+        {
+            self.spawn_enemy();
+        }
+        self.wave_asteroids_left -= 1;
+        if self.wave_asteroids_left == 0 {
+            self.wave_break_time = self.tuning.waves.break_duration;
+        }
+    }
+
+    fn spawn_enemy(&mut self) {
+        let wave = self.tuning.waves.wave(self.wave);
+        let weights: Vec<f32> = wave
+            .enemy_weights
+            .iter()
+            .map(|(_, weight)| *weight)
+            .collect();
+        let kind = wave.enemy_weights[self.rng.pick_weighted(&weights)].0;
+        let angle = self.rng.gen_range(0.0, PI * 2.0);
+        let position = self.ship.position()
+            + Vec2::from_angle(angle) * self.tuning.asteroids.generate_distance;
+        self.enemies.push(Enemy::new(
+            self.entity_ids.allocate(),
+            kind,
+            position,
+            &self.tuning.enemies,
+            &mut self.rng,
+        ));
+        self.wave_enemies_left -= 1;
+        self.events.push(GameEvent::EnemySpawned { kind, position });
+    }
+
+    fn spawn_boss(&mut self) {
+        let angle = self.rng.gen_range(0.0, PI * 2.0);
+        let position = self.ship.position()
+            + Vec2::from_angle(angle) * self.tuning.asteroids.generate_distance;
+        self.boss = Some(Boss::new(position, &self.tuning.boss, &mut self.entity_ids));
+        self.events.push(GameEvent::BossSpawned { position });
+    }
+
+    fn handle_weapons(&mut self, input: &PlayerInput, dt: f32) {
+        let weapons = &self.tuning.weapons;
+        let can_fire = self.ship.is_alive();
+
+        let power_ups = &self.tuning.power_ups;
+
+        if self.bullet_reload > 0.0 {
+            self.bullet_reload -= dt;
+        } else if input.fire_bullet && can_fire {
+            self.bullet_reload = weapons.bullet_reload;
+            if self.power_ups.is_active(PickupKind::RapidFire) {
+                self.bullet_reload *= power_ups.rapid_fire_reload_multiplier;
+            }
+            let count = if self.power_ups.is_active(PickupKind::SpreadShot) {
+                power_ups.spread_shot_bullets
+            } else {
+                1
+            };
+            for offset in fan(count, power_ups.spread_shot_angle) {
+                self.bullets.push(self.ship.fire(offset));
+            }
+        }
+
+        if self.rocket_reload > 0.0 {
+            self.rocket_reload -= dt;
+        } else if input.launch_rocket && can_fire && self.rocket_ammo > 0 {
+            let count = if self.power_ups.is_active(PickupKind::TripleRockets) {
+                3
+            } else {
+                1
+            };
+            for offset in fan(count, power_ups.triple_rockets_angle) {
+                let mut rocket = self.ship.launch_rocket(offset);
+                rocket.lock(rockets::lock_target(
+                    self.targets.as_slice(),
+                    rocket.position(),
+                    rocket.direction(),
+                    weapons,
+                ));
+                self.rockets.push(rocket);
+            }
+            self.rocket_ammo -= 1;
+            self.rocket_reload = weapons.rocket_reload;
+        }
+
+        if self.drone_reload > 0.0 {
+            self.drone_reload -= dt;
+        } else if input.spawn_drone && can_fire && self.drone_ammo > 0 {
+            self.drones
+                .push(self.ship.spawn_drone(&self.tuning.drone, &mut self.rng));
+            self.drone_ammo -= 1;
+            self.drone_reload = weapons.drone_reload;
+        }
+
+        if self.mine_reload > 0.0 {
+            self.mine_reload -= dt;
+        } else if input.spawn_mine && can_fire && self.mine_ammo > 0 {
+            self.mines.push(self.ship.spawn_mine());
+            self.mine_ammo -= 1;
+            self.mine_reload = weapons.mine_reload;
+        }
+    }
+
+    fn update_game_objects(&mut self, input: &PlayerInput, dt: f32) {
+        self.rockets.update(
+            &self.targets,
+            &self.tuning.weapons,
+            &self.tuning.rocket,
+            dt,
+            &mut self.fx_rng,
+            &mut self.rocket_blasts,
+        );
+        self.ship
+            .update(input, &self.tuning.ship, dt, &mut self.fx_rng);
+        self.bullets.iter_mut().for_each(|bullet| bullet.update(dt));
+        self.asteroids
+            .iter_mut()
+            .for_each(|asteroid| asteroid.update(dt));
+        self.explosions.update(dt);
+        self.pickups.iter_mut().for_each(|pickup| pickup.update(dt));
+        self.power_ups.update(dt);
+        if self.power_ups.is_active(PickupKind::DroneRecharge) {
+            self.drones.iter_mut().for_each(|drone| drone.recharge());
+        }
+        self.drones.iter_mut().for_each(|drone| {
+            let targets = self
+                .asteroids
+                .iter()
+                .map(|asteroid| asteroid.position())
+                .chain(self.enemies.iter().map(|enemy| enemy.position()))
+                .chain(self.boss.iter().flat_map(|boss| boss.targets()));
+            if let Some(bullet) = drone.fire(targets, &self.tuning.drone) {
+                self.bullets.push(bullet);
+            }
+        });
+        let target = self.ship.position();
+        for enemy in &mut self.enemies {
+            enemy.update(target, &self.tuning.enemies, dt);
+            if !self.ship.is_alive() {
+                continue;
+            }
+            if let Some(bullet) = enemy.fire(target, &self.tuning.enemies, &mut self.rng) {
+                self.bullets.push(bullet);
+            }
+        }
+        self.drones.iter_mut().for_each(|drone| {
+            drone.update(dt, self.ship.position(), &self.tuning.drone, &mut self.rng)
+        });
+        self.update_boss(dt);
+    }
+
+    /// Moves the boss and fires its turrets, or plays out its break-up once
+    /// the core is gone.
+    fn update_boss(&mut self, dt: f32) {
+        let Some(boss) = &mut self.boss else {
+            return;
+        };
+        let target = self.ship.position();
+        let phase = boss.phase();
+        boss.update(target, &self.tuning.boss, dt);
+        if boss.phase() != phase {
+            self.events.push(GameEvent::BossPhaseChanged {
+                phase: boss.phase(),
+                position: boss.position(),
+            });
+        }
+        if self.ship.is_alive() {
+            boss.fire(target, &self.tuning.boss, &mut self.bullets);
+        }
+
+        if let Some(position) = boss.death_blast(dt, &mut self.fx_rng) {
+            self.explosions
+                .explode(position, BOSS_BLAST_POWER, &mut self.fx_rng);
+        }
+        if boss.is_finished() {
+            self.explosions
+                .explode(boss.position(), BOSS_EXPLOSION_POWER, &mut self.fx_rng);
+            boss.part_ids().for_each(|id| self.entity_ids.release(id));
+            self.boss = None;
+        }
+    }
+
+    fn rebuild_asteroid_grid(&mut self) {
+        self.grid.clear();
+        for (index, asteroid) in self.asteroids.iter().enumerate() {
+            self.grid
+                .insert(index, asteroid.position(), asteroid.radius());
+        }
+    }
+
+    /// `impact` points the way the hit pushes the asteroid; its pieces fly
+    /// off in that direction.
+    fn destroy_asteroid(&mut self, index: usize, cause: DestroyCause, impact: Vec2) {
+        let asteroid = &mut self.asteroids[index];
+        let destroyed = asteroid.destroy(
+            impact,
+            &self.tuning.asteroids,
+            &mut self.new_asteroids,
+            &mut self.rng,
+        );
+        let (position, size, material) =
+            (asteroid.position(), asteroid.size(), asteroid.material());
+        if destroyed {
+            self.events.push(GameEvent::AsteroidDestroyed {
+                size,
+                cause,
+                position,
+            });
+            if let Some(kind) = self.roll_drop(size, material) {
+                self.pickups.push(Pickup::new(
+                    position,
+                    kind,
+                    self.tuning.pickups.lifetime,
+                    &mut self.rng,
+                ));
+            }
+        }
+        self.explosions.explode(position, size, &mut self.fx_rng);
+        if destroyed && material == Material::Explosive {
+            self.detonate(position, cause);
+        }
+    }
+
+    /// Index of the boss part a shot at `position` lands on, if any.
+    fn boss_part_at(&self, position: Vec2, radius: f32) -> Option<usize> {
+        self.boss
+            .as_ref()
+            .and_then(|boss| boss.part_at(position, radius))
+    }
+
+    /// Damages the boss part at `index`, which the shield may protect.
+    fn hit_boss(&mut self, index: usize, position: Vec2, damage: u32, cause: DestroyCause) {
+        let Some(boss) = &mut self.boss else {
+            return;
+        };
+        if !boss.damage_part(index, damage) {
+            self.explosions.explode(position, 1, &mut self.fx_rng);
+            return;
+        }
+        let (kind, part_position) = (boss.part(index).kind(), boss.part_position(index));
+        self.explosions
+            .explode(part_position, BOSS_BLAST_POWER, &mut self.fx_rng);
+        if kind == BossPartKind::Core {
+            self.events.push(GameEvent::BossDestroyed {
+                cause,
+                position: boss.position(),
+            });
+        } else {
+            self.events.push(GameEvent::BossPartDestroyed {
+                kind,
+                cause,
+                position: part_position,
+            });
+        }
+    }
+
+    fn destroy_enemy(&mut self, index: usize, cause: DestroyCause) {
+        let enemy = &mut self.enemies[index];
+        if !enemy.destroy() {
+            return;
+        }
+        let (kind, position) = (enemy.kind(), enemy.position());
+        self.explosions
+            .explode(position, ENEMY_EXPLOSION_POWER, &mut self.fx_rng);
+        self.events.push(GameEvent::EnemyDestroyed {
+            kind,
+            cause,
+            position,
+        });
+    }
+
+    /// Applies enemy `damage` to the ship, unless the shield or hit
+    /// invulnerability protects it; a ram also knocks it away from `rammer`.
+    fn damage_ship(&mut self, damage: f32, rammer: Option<Vec2>) {
+        if self.power_ups.is_active(PickupKind::Shield) || !self.ship.is_vulnerable() {
+            return;
+        }
+        match rammer {
+            Some(position) => self.ship.explosion_hit(position, damage, &self.tuning.ship),
+            None => self.ship.bullet_hit(damage, &self.tuning.ship),
+        }
+        self.events.push(GameEvent::ShipDamaged {
+            damage,
+            position: self.ship.position(),
+        });
+        if self.ship.hull() <= 0.0 {
+            self.destroy_ship();
+        }
+    }
+
+    /// Crate left behind by a destroyed asteroid, if any.
+    fn roll_drop(&mut self, size: u8, material: Material) -> Option<PickupKind> {
+        if material == Material::Crystalline {
+            let chance = self.tuning.asteroids.crystalline_drop_chance;
+            return (self.rng.next_f32() < chance).then_some(PickupKind::Supply);
+        }
+        let table = self.tuning.pickups.drop_table(size)?;
+        if self.rng.next_f32() >= table.chance {
+            return None;
+        }
+        let weights: Vec<f32> = table.kinds.iter().map(|(_, weight)| *weight).collect();
+        Some(table.kinds[self.rng.pick_weighted(&weights)].0)
+    }
+
+    /// Blast of an explosive asteroid: destroys the asteroids it reaches,
+    /// credited to whatever set it off, and damages the ship.
+    fn detonate(&mut self, center: Vec2, cause: DestroyCause) {
+        let radius = self.tuning.asteroids.explosive_radius;
+        self.explosions
+            .explode(center, EXPLOSIVE_ASTEROID_POWER, &mut self.fx_rng);
+
+        let mut neighbours = Vec::new();
+        self.grid.query(center, radius, &mut neighbours);
+        for index in neighbours {
+            let asteroid = &self.asteroids[index];
+            if asteroid.is_alive() && asteroid.circle_collision(center, radius) {
+                let impact = asteroid.position() - center;
+                self.destroy_asteroid(index, cause, impact);
+            }
+        }
+
+        let reach = radius + self.ship.collision_radius();
+        if self.ship.is_vulnerable()
+            && !self.power_ups.is_active(PickupKind::Shield)
+            && self.ship.position().distance(center) <= reach
+        {
+            let damage = self.tuning.asteroids.explosive_damage;
+            self.ship.explosion_hit(center, damage, &self.tuning.ship);
+            self.events.push(GameEvent::ShipDamaged {
+                damage,
+                position: self.ship.position(),
+            });
+            if self.ship.hull() <= 0.0 {
+                self.destroy_ship();
+            }
+        }
+    }
+
+    /// Blast of a rocket that went off near its target or self-destructed:
+    /// destroys the asteroids and enemies it reaches and hits the boss part
+    /// it catches.
+    fn detonate_rocket(&mut self, center: Vec2) {
+        let radius = self.tuning.rocket.blast_radius;
+        self.explosions
+            .explode(center, ROCKET_BLAST_POWER, &mut self.fx_rng);
+        self.events
+            .push(GameEvent::RocketDetonated { position: center });
+
+        let mut neighbours = Vec::new();
+        self.grid.query(center, radius, &mut neighbours);
+        for index in neighbours {
+            let asteroid = &self.asteroids[index];
+            if asteroid.is_alive() && asteroid.circle_collision(center, radius) {
+                let impact = asteroid.position() - center;
+                self.destroy_asteroid(index, DestroyCause::Rocket, impact);
+            }
+        }
+
+        for index in 0..self.enemies.len() {
+            if self.enemies[index].collision(center, radius) {
+                self.destroy_enemy(index, DestroyCause::Rocket);
+            }
+        }
+
+        if let Some(index) = self.boss_part_at(center, radius) {
+            let damage = self.tuning.boss.rocket_damage;
+            self.hit_boss(index, center, damage, DestroyCause::Rocket);
+        }
+    }
+
+    fn process_collisions(&mut self, dt: f32) {
+        self.rebuild_asteroid_grid();
+        let mut candidates = std::mem::take(&mut self.candidates);
+
+        for i in 0..self.bullets.len() {
+            let bullet = &self.bullets[i];
+            if !bullet.alive() {
+                continue;
+            }
+            self.grid
+                .query(bullet.position(), bullet.radius(), &mut candidates);
+            for &j in &candidates {
+                let bullet = &self.bullets[i];
+                if !self.asteroids[j].is_alive()
+                    || !self.asteroids[j].circle_collision(bullet.position(), bullet.radius())
+                {
+                    continue;
+                }
+                let (position, cause, impact) =
+                    (bullet.position(), bullet.cause(), bullet.direction());
+                self.bullets[i].destroy();
+                if self.asteroids[j].bullet_hit(cause) {
+                    self.destroy_asteroid(j, cause, impact);
+                } else {
+                    self.explosions.explode(position, 1, &mut self.fx_rng);
+                }
+                break;
+            }
+        }
+
+        for i in 0..self.asteroids.len() {
+            let asteroid = &self.asteroids[i];
+            self.grid.query(
+                asteroid.position(),
+                asteroid.radius() * 2.0,
+                &mut candidates,
+            );
+            for &j in &candidates {
+                if j <= i || !self.asteroids[i].is_alive() || !self.asteroids[j].is_alive() {
+                    continue;
+                }
+                if !self.asteroids[i].collision(&self.asteroids[j]) {
+                    continue;
+                }
+                let tuning = &self.tuning.asteroids;
+                if self.asteroids[i].impact_energy(&self.asteroids[j]) > tuning.break_energy {
+                    let impact = self.asteroids[j].position() - self.asteroids[i].position();
+                    self.destroy_asteroid(i, DestroyCause::Collision, -impact);
+                    self.destroy_asteroid(j, DestroyCause::Collision, impact);
+                } else {
+                    let (left, right) = self.asteroids.split_at_mut(j);
+                    left[i].bounce(&mut right[0], tuning);
+                }
+            }
+        }
+
+        self.grid.query(
+            self.ship.position(),
+            self.ship.collision_radius(),
+            &mut candidates,
+        );
+        let is_shielded = self.power_ups.is_active(PickupKind::Shield);
+        for &i in &candidates {
+            // The ship may have been destroyed by an earlier candidate.
+            if !self.ship.is_alive() {
+                break;
+            }
+            if !self.asteroids[i].is_alive() || !self.ship.asteroid_collision(&self.asteroids[i]) {
+                continue;
+            }
+            let impact = self.asteroids[i].position() - self.ship.position();
+            if is_shielded {
+                self.destroy_asteroid(i, DestroyCause::Collision, impact);
+            } else if self.ship.is_vulnerable() {
+                let asteroid = &self.asteroids[i];
+                let damage = self.ship.asteroid_hit(asteroid, &self.tuning.ship);
+                self.events.push(GameEvent::ShipDamaged {
+                    damage,
+                    position: self.ship.position(),
+                });
+                if asteroid.size() <= self.tuning.ship.max_breakable_size {
+                    self.destroy_asteroid(i, DestroyCause::Collision, impact);
+                }
+                if self.ship.hull() <= 0.0 {
+                    self.destroy_ship();
+                }
+            }
+        }
+
+        for i in 0..self.rockets.len() {
+            let rocket = self.rockets.get(i);
+            if !rocket.is_alive() {
+                continue;
+            }
+            self.grid.query(
+                rocket.position(),
+                rocket.collision_radius(),
+                &mut candidates,
+            );
+            for &j in &candidates {
+                let rocket = self.rockets.get_mut(i);
+                if !self.asteroids[j].is_alive() || !rocket.asteroid_collision(&self.asteroids[j]) {
+                    continue;
+                }
+                rocket.destroy();
+                self.events.push(GameEvent::RocketHit {
+                    position: rocket.position(),
+                });
+                let impact = rocket.direction();
+                self.destroy_asteroid(j, DestroyCause::Rocket, impact);
+                break;
+            }
+        }
+
+        for i in 0..self.drones.len() {
+            let drone = &self.drones[i];
+            if !drone.is_alive() {
+                continue;
+            }
+            self.grid
+                .query(drone.position(), drone.asteroid_reach(), &mut candidates);
+            for &j in &candidates {
+                if !self.asteroids[j].is_alive() {
+                    continue;
+                }
+                if self.drones[i].asteroid_collision(&self.asteroids[j], dt) {
+                    let impact = self.asteroids[j].position() - self.drones[i].position();
+                    self.destroy_asteroid(j, DestroyCause::Drone, impact);
+                    self.explosions.explode(
+                        self.drones[i].position(),
+                        DRONE_EXPLOSION_POWER,
+                        &mut self.fx_rng,
+                    );
+                    break;
+                }
+            }
+        }
+
+        for i in 0..self.mines.len() {
+            let mine = &self.mines[i];
+            if !mine.is_alive() {
+                continue;
+            }
+            self.grid
+                .query(mine.position(), mine.radius(), &mut candidates);
+            for &j in &candidates {
+                if !self.asteroids[j].is_alive() {
+                    continue;
+                }
+                if self.mines[i].asteroid_collision(&self.asteroids[j]) {
+                    let impact = self.asteroids[j].position() - self.mines[i].position();
+                    self.destroy_asteroid(j, DestroyCause::Mine, impact);
+                    self.explosions.explode(
+                        self.mines[i].position(),
+                        MINE_EXPLOSION_POWER,
+                        &mut self.fx_rng,
+                    );
+                    self.mines[i].destroy();
+                    self.events.push(GameEvent::MineDetonated {
+                        position: self.mines[i].position(),
+                    });
+                    break;
+                }
+            }
+        }
+
+        let mut rocket_blasts = std::mem::take(&mut self.rocket_blasts);
+        for position in rocket_blasts.drain(..) {
+            self.detonate_rocket(position);
+        }
+        self.rocket_blasts = rocket_blasts;
+
+        self.process_enemy_collisions(dt, &mut candidates);
+        self.process_boss_collisions(&mut candidates);
+
+        for i in 0..self.pickups.len() {
+            let pickup = &self.pickups[i];
+            if self.ship.is_alive() && self.ship.pickup_collision(pickup) {
+                let (kind, position) = (pickup.kind(), pickup.position());
+                self.pickups[i].collect();
+                self.collect_pickup(kind);
+                self.events
+                    .push(GameEvent::PickupCollected { kind, position });
+            }
+        }
+
+        for asteroid in &mut self.asteroids {
+            if asteroid.position().distance(self.ship.position())
+                >= self.tuning.asteroids.despawn_distance
+            {
+                asteroid.despawn();
+                self.events.push(GameEvent::AsteroidDespawned {
+                    position: asteroid.position(),
+                });
+            }
+        }
+
+        self.grid.clear();
+        for (index, drone) in self.drones.iter().enumerate() {
+            self.grid
+                .insert(index, drone.position(), drone.drone_reach());
+        }
+        for i in 0..self.drones.len() {
+            self.grid
+                .query(self.drones[i].position(), 0.0, &mut candidates);
+            for &j in &candidates {
+                if i != j {
+                    let position = self.drones[j].position();
+                    self.drones[i].drone_collision(position, dt);
+                }
+            }
+        }
+
+        self.candidates = candidates;
+
+        self.asteroids.reserve(self.new_asteroids.len());
+        while let Some(new_asteroid) = self.new_asteroids.pop() {
+            self.add_asteroid(new_asteroid);
+        }
+    }
+
+    /// Enemies are few, so apart from asteroids they are checked against
+    /// everything directly rather than through the grid.
+    fn process_enemy_collisions(&mut self, dt: f32, candidates: &mut Vec<usize>) {
+        for i in 0..self.enemies.len() {
+            let enemy = &self.enemies[i];
+            self.grid
+                .query(enemy.position(), enemy.asteroid_reach(), candidates);
+            for &j in candidates.iter() {
+                if self.enemies[i].is_alive()
+                    && self.enemies[i].asteroid_collision(&self.asteroids[j], dt)
+                {
+                    self.destroy_enemy(i, DestroyCause::Collision);
+                }
+            }
+        }
+
+        for i in 0..self.bullets.len() {
+            let bullet = &self.bullets[i];
+            if !bullet.alive() {
+                continue;
+            }
+            let (position, radius) = (bullet.position(), bullet.radius());
+            match bullet.faction() {
+                Faction::Player => {
+                    let cause = bullet.cause();
+                    let Some(j) = self
+                        .enemies
+                        .iter()
+                        .position(|enemy| enemy.is_alive() && enemy.collision(position, radius))
+                    else {
+                        continue;
+                    };
+                    self.bullets[i].destroy();
+                    if self.enemies[j].bullet_hit() {
+                        self.destroy_enemy(j, cause);
+                    } else {
+                        self.explosions.explode(position, 1, &mut self.fx_rng);
+                    }
+                }
+                Faction::Enemy => {
+                    let damage = bullet.damage();
+                    if self.ship.circle_collision(position, radius) {
+                        self.bullets[i].destroy();
+                        self.explosions.explode(position, 1, &mut self.fx_rng);
+                        self.damage_ship(damage, None);
+                    } else if let Some(drone) = self.drones.iter_mut().find(|drone| {
+                        drone.position().distance(position) <= drone.radius() + radius
+                    }) {
+                        self.bullets[i].destroy();
+                        drone.destroy();
+                    }
+                }
+            }
+        }
+
+        for i in 0..self.rockets.len() {
+            let rocket = self.rockets.get(i);
+            let (position, radius) = (rocket.position(), rocket.collision_radius());
+            if !rocket.is_alive() {
+                continue;
+            }
+            if let Some(j) = self
+                .enemies
+                .iter()
+                .position(|enemy| enemy.is_alive() && enemy.collision(position, radius))
+            {
+                self.rockets.get_mut(i).destroy();
+                self.events.push(GameEvent::RocketHit { position });
+                self.destroy_enemy(j, DestroyCause::Rocket);
+            }
+        }
+
+        for i in 0..self.mines.len() {
+            let mine = &self.mines[i];
+            let (position, radius) = (mine.position(), mine.radius());
+            if !mine.is_alive() {
+                continue;
+            }
+            if let Some(j) = self
+                .enemies
+                .iter()
+                .position(|enemy| enemy.is_alive() && enemy.collision(position, radius))
+            {
+                self.destroy_enemy(j, DestroyCause::Mine);
+                self.explosions
+                    .explode(position, MINE_EXPLOSION_POWER, &mut self.fx_rng);
+                self.mines[i].destroy();
+                self.events.push(GameEvent::MineDetonated { position });
+            }
+        }
+
+        for i in 0..self.enemies.len() {
+            let enemy = &self.enemies[i];
+            if !enemy.is_alive() || !self.ship.circle_collision(enemy.position(), enemy.radius()) {
+                continue;
+            }
+            let position = enemy.position();
+            let damage = self.tuning.enemies.kind(enemy.kind()).ram_damage;
+            self.destroy_enemy(i, DestroyCause::Collision);
+            self.damage_ship(damage, Some(position));
+        }
+    }
+
+    /// The boss is one compound object: asteroids and the ship collide with
+    /// its hull, while shots hit its parts one by one.
+    fn process_boss_collisions(&mut self, candidates: &mut Vec<usize>) {
+        let Some(boss) = &self.boss else {
+            return;
+        };
+        if boss.is_dying() {
+            return;
+        }
+        let (center, radius) = (boss.position(), boss.radius());
+
+        self.grid.query(center, radius, candidates);
+        for &j in candidates.iter() {
+            let asteroid = &self.asteroids[j];
+            if asteroid.is_alive() && asteroid.circle_collision(center, radius) {
+                let impact = asteroid.position() - center;
+                self.destroy_asteroid(j, DestroyCause::Enemy, impact);
+            }
+        }
+
+        for i in 0..self.bullets.len() {
+            let bullet = &self.bullets[i];
+            let (position, bullet_radius) = (bullet.position(), bullet.radius());
+            if !bullet.alive() || bullet.faction() != Faction::Player {
+                continue;
+            }
+            let Some(index) = self.boss_part_at(position, bullet_radius) else {
+                continue;
+            };
+            let cause = bullet.cause();
+            self.bullets[i].destroy();
+            self.hit_boss(index, position, 1, cause);
+        }
+
+        for i in 0..self.rockets.len() {
+            let rocket = self.rockets.get(i);
+            let (position, rocket_radius) = (rocket.position(), rocket.collision_radius());
+            if !rocket.is_alive() {
+                continue;
+            }
+            let Some(index) = self.boss_part_at(position, rocket_radius) else {
+                continue;
+            };
+            self.rockets.get_mut(i).destroy();
+            self.events.push(GameEvent::RocketHit { position });
+            let damage = self.tuning.boss.rocket_damage;
+            self.hit_boss(index, position, damage, DestroyCause::Rocket);
+        }
+
+        for i in 0..self.mines.len() {
+            let mine = &self.mines[i];
+            let (position, mine_radius) = (mine.position(), mine.radius());
+            if !mine.is_alive() {
+                continue;
+            }
+            let Some(index) = self.boss_part_at(position, mine_radius) else {
+                continue;
+            };
+            self.mines[i].destroy();
+            self.explosions
+                .explode(position, MINE_EXPLOSION_POWER, &mut self.fx_rng);
+            self.events.push(GameEvent::MineDetonated { position });
+            let damage = self.tuning.boss.mine_damage;
+            self.hit_boss(index, position, damage, DestroyCause::Mine);
+        }
+
+        if self.ship.circle_collision(center, radius) {
+            self.damage_ship(self.tuning.boss.ram_damage, Some(center));
+        }
+    }
+
+    fn remove_objects(&mut self) {
+        self.bullets.retain(|bullet| bullet.alive());
+        let entity_ids = &mut self.entity_ids;
+        self.asteroids.retain(|asteroid| {
+            if !asteroid.is_alive() {
+                entity_ids.release(asteroid.id());
+            }
+            asteroid.is_alive()
+        });
+        self.drones.retain(|drone| {
+            if drone.is_alive() {
+                true
+            } else {
+                self.explosions
+                    .explode(drone.position(), DRONE_EXPLOSION_POWER, &mut self.fx_rng);
+                self.events.push(GameEvent::DroneLost {
+                    position: drone.position(),
+                });
+                false
+            }
+        });
+        let entity_ids = &mut self.entity_ids;
+        self.enemies.retain(|enemy| {
+            if !enemy.is_alive() {
+                entity_ids.release(enemy.id());
+            }
+            enemy.is_alive()
+        });
+        self.mines.retain(|mine| mine.is_alive());
+        self.pickups.retain(|pickup| pickup.is_alive());
+    }
+
+    fn collect_pickup(&mut self, kind: PickupKind) {
+        let weapons = &self.tuning.weapons;
+        let pickups = &self.tuning.pickups;
+        match kind {
+            PickupKind::RapidFire
+            | PickupKind::SpreadShot
+            | PickupKind::Shield
+            | PickupKind::DroneRecharge
+            | PickupKind::TripleRockets => {
+                self.power_ups.activate(kind, &self.tuning.power_ups);
+            }
+            PickupKind::Supply => {
+                self.rocket_ammo =
+                    (self.rocket_ammo + pickups.supply_rockets).min(weapons.max_rockets);
+                self.drone_ammo = (self.drone_ammo + pickups.supply_drones).min(weapons.max_drones);
+                self.mine_ammo = (self.mine_ammo + pickups.supply_mines).min(weapons.max_mines);
+            }
+        }
+    }
+
+    /// Blows up the ship and either schedules its respawn or ends the run
+    /// when no lives are left.
+    fn destroy_ship(&mut self) {
+        let position = self.ship.position();
+        self.ship.destroy();
+        self.explosions
+            .explode(position, SHIP_EXPLOSION_POWER, &mut self.fx_rng);
+        self.events.push(GameEvent::ShipHit { position });
+        self.lives = self.lives.saturating_sub(1);
+        if self.lives == 0 {
+            self.is_game_over = true;
+        } else {
+            self.respawn_time = self.tuning.ship.respawn_delay;
+        }
+    }
+
+    fn update_respawn(&mut self, dt: f32) {
+        if self.ship.is_alive() || self.is_game_over {
+            return;
+        }
+        self.respawn_time -= dt;
+        if self.respawn_time > 0.0 {
+            return;
+        }
+        self.ship.respawn(&self.tuning.ship);
+        let center = self.ship.position();
+        let clear_radius = self.tuning.ship.respawn_clear_radius;
+        for asteroid in &mut self.asteroids {
+            if asteroid.position().distance(center) < clear_radius + asteroid.radius() {
+                asteroid.push_away(center, clear_radius);
+            }
+        }
+    }
+
+    /// Advances the world by `dt` seconds using the given player controls.
+    pub fn update(&mut self, input: &PlayerInput, dt: f32) {
+        self.events.clear();
+        self.update_respawn(dt);
+        self.generate_asteroid(dt);
+        self.rebuild_targets();
+        self.handle_weapons(input, dt);
+        self.update_game_objects(input, dt);
+        self.process_collisions(dt);
+        self.remove_objects();
+        for event in &self.events {
+            self.score.on_event(event);
+        }
+    }
+
+    /// Places an extra asteroid into the world, e.g. to build a scenario.
+    pub fn add_asteroid(&mut self, asteroid: Asteroid) {
+        let id = self.entity_ids.allocate();
+        self.asteroids.push(asteroid.with_id(id));
+    }
+
+    fn rebuild_targets(&mut self) {
+        let asteroids = self.asteroids.iter().map(|asteroid| Target {
+            id: asteroid.id(),
+            position: asteroid.position(),
+            velocity: asteroid.velocity(),
+            radius: asteroid.radius(),
+        });
+        let enemies = self.enemies.iter().map(|enemy| Target {
+            id: enemy.id(),
+            position: enemy.position(),
+            velocity: enemy.velocity(),
+            radius: enemy.radius(),
+        });
+        let boss_parts = self.boss.iter().flat_map(|boss| boss.rocket_targets());
+        self.targets
+            .rebuild(asteroids.chain(enemies).chain(boss_parts));
+    }
+
+    pub fn tuning(&self) -> &Tuning {
+        &self.tuning
+    }
+
+    /// Applies new balance values to the running game.
+    pub fn set_tuning(&mut self, tuning: Tuning) {
+        self.score.set_tuning(tuning.score.clone());
+        self.tuning = tuning;
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn ship(&self) -> &Ship {
+        &self.ship
+    }
+
+    pub fn bullets(&self) -> &[Bullet] {
+        &self.bullets
+    }
+
+    pub fn asteroids(&self) -> &[Asteroid] {
+        &self.asteroids
+    }
+
+    pub fn rockets(&self) -> &Rockets {
+        &self.rockets
+    }
+
+    /// Asteroids, enemies and boss parts rockets could lock onto as of the
+    /// last update.
+    pub fn targets(&self) -> &Targets {
+        &self.targets
+    }
+
+    pub fn drones(&self) -> &[Drone] {
+        &self.drones
+    }
+
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    pub fn boss(&self) -> Option<&Boss> {
+        self.boss.as_ref()
+    }
+
+    pub fn mines(&self) -> &[Mine] {
+        &self.mines
+    }
+
+    pub fn power_ups(&self) -> &PowerUps {
+        &self.power_ups
+    }
+
+    pub fn pickups(&self) -> &[Pickup] {
+        &self.pickups
+    }
+
+    pub fn explosions(&self) -> &Explosions {
+        &self.explosions
+    }
+
+    /// Events produced by the last call to `update`.
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events
+    }
+
+    /// Number of the current wave; 0 before the first one starts.
+    pub fn wave(&self) -> u32 {
+        self.wave
+    }
+
+    pub fn lives(&self) -> u32 {
+        self.lives
+    }
+
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    pub fn bullet_reload(&self) -> f32 {
+        self.bullet_reload
+    }
+
+    pub fn rocket_ammo(&self) -> u32 {
+        self.rocket_ammo
+    }
+
+    pub fn drone_ammo(&self) -> u32 {
+        self.drone_ammo
+    }
+
+    pub fn mine_ammo(&self) -> u32 {
+        self.mine_ammo
+    }
+
+    pub fn rocket_reload(&self) -> f32 {
+        self.rocket_reload
+    }
+
+    pub fn drone_reload(&self) -> f32 {
+        self.drone_reload
+    }
+
+    pub fn mine_reload(&self) -> f32 {
+        self.mine_reload
+    }
+
+    pub fn particles_count(&self) -> usize {
+        self.explosions.particles_count()
+            + self.ship.particles_count()
+            + self.rockets.particles_count()
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.is_game_over
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(seed: u64) -> Simulation {
+        let mut simulation = Simulation::new(seed, Tuning::default());
+        for tick in 0..1200u32 {
+            let input = PlayerInput::from_bits((tick / 45 % 256) as u8);
+            simulation.update(&input, TICK_DURATION);
+        }
+        simulation
+    }
+
+    /// Two size-one rock asteroids on the same spot, far from the ship.
+    fn overlapping_asteroids() -> (Simulation, Vec2) {
+        let mut simulation = Simulation::new(1, Tuning::default());
+        let position = simulation.ship().position() + vec2(1000.0, 0.0);
+        let mut rng = Rng::new(1);
+        for _ in 0..2 {
+            simulation.add_asteroid(Asteroid::with_size(position, Vec2::ZERO, 1, &mut rng));
+        }
+        (simulation, position)
+    }
+
+    fn count(simulation: &Simulation, matches: impl Fn(&GameEvent) -> bool) -> usize {
+        simulation
+            .events()
+            .iter()
+            .filter(|event| matches(event))
+            .count()
+    }
+
+    #[test]
+    fn bullet_destroys_only_one_of_overlapping_asteroids() {
+        let (mut simulation, position) = overlapping_asteroids();
+        simulation
+            .bullets
+            .push(Bullet::new(position, 0.0, DestroyCause::Bullet));
+        simulation.update(&PlayerInput::default(), TICK_DURATION);
+        let destroyed = count(&simulation, |event| {
+            matches!(event, GameEvent::AsteroidDestroyed { .. })
+        });
+        assert_eq!(destroyed, 1);
+        assert!(simulation.bullets().is_empty());
+        assert_eq!(simulation.asteroids().len(), 1);
+    }
+
+    #[test]
+    fn mine_detonates_once_on_overlapping_asteroids() {
+        let (mut simulation, position) = overlapping_asteroids();
+        simulation.mines.push(Mine::new(position));
+        simulation.update(&PlayerInput::default(), TICK_DURATION);
+        let destroyed = count(&simulation, |event| {
+            matches!(event, GameEvent::AsteroidDestroyed { .. })
+        });
+        let detonated = count(&simulation, |event| {
+            matches!(event, GameEvent::MineDetonated { .. })
+        });
+        assert_eq!((destroyed, detonated), (1, 1));
+        assert!(simulation.mines().is_empty());
+    }
+
+    #[test]
+    fn asteroid_shot_this_tick_does_not_hit_ship() {
+        let mut simulation = Simulation::new(1, Tuning::default());
+        let position = simulation.ship().position();
+        let mut rng = Rng::new(1);
+        simulation.add_asteroid(Asteroid::with_size(position, Vec2::ZERO, 1, &mut rng));
+        simulation
+            .bullets
+            .push(Bullet::new(position, 0.0, DestroyCause::Bullet));
+        simulation.update(&PlayerInput::default(), TICK_DURATION);
+        let damaged = count(&simulation, |event| {
+            matches!(event, GameEvent::ShipDamaged { .. })
+        });
+        assert_eq!(damaged, 0);
+        assert!(simulation.asteroids().is_empty());
+    }
+
+    #[test]
+    fn same_seed_and_input_give_same_state() {
+        let state = |simulation: &Simulation| ron::to_string(simulation).unwrap();
+        assert_eq!(state(&run(5)), state(&run(5)));
+        assert_ne!(state(&run(5)), state(&run(6)));
+    }
+}
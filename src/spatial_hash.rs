@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasherDefault, Hasher},
+};
+
+use macroquad::prelude::*;
+
+/// Multiplicative hash for cell coordinates; much cheaper than the default
+/// SipHash, and the keys are not attacker controlled.
+#[derive(Default)]
+struct CellHasher {
+    hash: u64,
+}
+
+impl Hasher for CellHasher {
+    fn finish(&self) -> u64 {
+        self.hash
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        bytes.iter().for_each(|byte| self.write_u64(*byte as u64));
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write_u64(value as u32 as u64);
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.hash = (self.hash.rotate_left(5) ^ value).wrapping_mul(0x517cc1b727220a95);
+    }
+}
+
+/// Uniform grid broad phase: objects are bucketed by the cells their bounds
+/// cover, so a query only visits objects in nearby cells.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>, BuildHasherDefault<CellHasher>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    fn cell_range(&self, position: Vec2, radius: f32) -> (i32, i32, i32, i32) {
+        let min = ((position - radius) / self.cell_size).floor();
+        let max = ((position + radius) / self.cell_size).floor();
+        (min.x as i32, min.y as i32, max.x as i32, max.y as i32)
+    }
+
+    /// Forgets all objects but keeps the buffers of recently used cells, so
+    /// rebuilding every tick does not reallocate them.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, cell| {
+            let is_used = !cell.is_empty();
+            cell.clear();
+            is_used
+        });
+    }
+
+    pub fn insert(&mut self, index: usize, position: Vec2, radius: f32) {
+        let (min_x, min_y, max_x, max_y) = self.cell_range(position, radius);
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                self.cells.entry((x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    /// Collects every object whose bounds may overlap the given circle into
+    /// `result`, each index once and in ascending order.
+    pub fn query(&self, position: Vec2, radius: f32, result: &mut Vec<usize>) {
+        result.clear();
+        let (min_x, min_y, max_x, max_y) = self.cell_range(position, radius);
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                if let Some(cell) = self.cells.get(&(x, y)) {
+                    result.extend_from_slice(cell);
+                }
+            }
+        }
+        result.sort_unstable();
+        result.dedup();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_finds_overlapping_cells_once() {
+        let mut grid = SpatialHash::new(100.0);
+        grid.insert(0, vec2(50.0, 50.0), 10.0);
+        grid.insert(1, vec2(100.0, 100.0), 80.0);
+        grid.insert(2, vec2(1000.0, 1000.0), 10.0);
+        grid.insert(3, vec2(-50.0, -50.0), 10.0);
+
+        let mut result = Vec::new();
+        grid.query(vec2(60.0, 60.0), 5.0, &mut result);
+        assert_eq!(result, vec![0, 1]);
+
+        grid.query(vec2(0.0, 0.0), 200.0, &mut result);
+        assert_eq!(result, vec![0, 1, 3]);
+
+        grid.query(vec2(500.0, 500.0), 10.0, &mut result);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn clear_forgets_objects() {
+        let mut grid = SpatialHash::new(100.0);
+        grid.insert(0, vec2(50.0, 50.0), 10.0);
+        grid.clear();
+        let mut result = vec![7];
+        grid.query(vec2(50.0, 50.0), 10.0, &mut result);
+        assert!(result.is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+use std::f32::consts::PI;
+
+use macroquad::prelude::*;
+
+/// Heading after turning from `angle` towards `target` for `dt` seconds.
+pub fn turn_towards(angle: f32, position: Vec2, target: Vec2, rotation_speed: f32, dt: f32) -> f32 {
+    let difference = Vec2::from_angle(angle).angle_between(position - target);
+    let mut angle = if difference > 0.0 {
+        angle - rotation_speed * dt
+    } else {
+        angle + rotation_speed * dt
+    };
+    while angle > PI * 2.0 {
+        angle -= PI * 2.0;
+    }
+    angle
+}
+
+/// Where something leaving `position` at `speed` can meet a target at
+/// `target` moving with `target_velocity`, if it can catch it at all.
+pub fn intercept(position: Vec2, speed: f32, target: Vec2, target_velocity: Vec2) -> Option<Vec2> {
+    let offset = target - position;
+    let a = target_velocity.length_squared() - speed * speed;
+    let b = 2.0 * offset.dot(target_velocity);
+    let c = offset.length_squared();
+    let time = if a.abs() < f32::EPSILON {
+        -c / b
+    } else {
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let root = discriminant.sqrt();
+        let (first, second) = ((-b - root) / (2.0 * a), (-b + root) / (2.0 * a));
+        if first > 0.0 && (first < second || second <= 0.0) {
+            first
+        } else {
+            second
+        }
+    };
+    (time > 0.0).then(|| target + target_velocity * time)
+}
+
+/// Speed after accelerating (or braking, for a negative `acceleration`)
+/// for `dt` seconds, kept between zero and `max_speed`.
+pub fn accelerate(speed: f32, acceleration: f32, max_speed: f32, dt: f32) -> f32 {
+    (speed + acceleration * dt).clamp(0.0, max_speed)
+}
+
+/// Position after sliding away from `obstacle` for `dt` seconds, if it is
+/// within `reach`.
+pub fn avoid(position: Vec2, obstacle: Vec2, reach: f32, speed: f32, dt: f32) -> Vec2 {
+    let direction = position - obstacle;
+    if direction.length() <= reach {
+        position + direction.normalize_or_zero() * speed * dt
+    } else {
+        position
+    }
+}
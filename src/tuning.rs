@@ -0,0 +1,1299 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asteroid::{Material, MAX_ASTEROID_SIZE},
+    enemy::EnemyKind,
+    events::DestroyCause,
+    pickup::PickupKind,
+    rocket::Guidance,
+};
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Balance values loaded from the tuning file. Anything missing from the
+/// file keeps its default, so designers only list what they change.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tuning {
+    pub weapons: WeaponTuning,
+    pub asteroids: AsteroidTuning,
+    pub ship: ShipTuning,
+    pub rocket: RocketTuning,
+    pub drone: DroneTuning,
+    pub enemies: EnemyTuning,
+    pub boss: BossTuning,
+    pub score: ScoreTuning,
+    pub waves: WaveTuning,
+    pub pickups: PickupTuning,
+    pub power_ups: PowerUpTuning,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WeaponTuning {
+    pub bullet_reload: f32,
+    pub rocket_reload: f32,
+    pub drone_reload: f32,
+    pub mine_reload: f32,
+    pub starting_rockets: u32,
+    pub starting_drones: u32,
+    pub starting_mines: u32,
+    pub max_rockets: u32,
+    pub max_drones: u32,
+    pub max_mines: u32,
+    /// Widest angle, in radians, off a rocket's heading at which it still
+    /// locks onto a target.
+    pub rocket_lock_angle: f32,
+    pub rocket_lock_distance: f32,
+}
+
+impl Default for WeaponTuning {
+    fn default() -> Self {
+        Self {
+            bullet_reload: 0.2,
+            rocket_reload: 0.5,
+            drone_reload: 1.0,
+            mine_reload: 0.6,
+            starting_rockets: 10,
+            starting_drones: 3,
+            starting_mines: 5,
+            max_rockets: 20,
+            max_drones: 6,
+            max_mines: 10,
+            rocket_lock_angle: 0.5,
+            rocket_lock_distance: 1500.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AsteroidTuning {
+    pub generate_distance: f32,
+    pub despawn_distance: f32,
+    /// Share of the closing speed kept when two asteroids bounce: 1 is
+    /// perfectly elastic, 0 makes them stick together.
+    pub restitution: f32,
+    /// How much of the sliding speed at the contact is turned into spin.
+    pub spin_transfer: f32,
+    /// Asteroids colliding with more impact energy than this break apart
+    /// instead of bouncing.
+    pub break_energy: f32,
+    /// Speed at which the pieces of a broken asteroid scatter.
+    pub fragment_speed: f32,
+    /// Speed added to the pieces along the direction of the hit.
+    pub impact_speed: f32,
+    /// Bullet hits a metallic asteroid takes before it breaks.
+    pub metallic_hit_points: u32,
+    /// Pieces an ice asteroid shatters into.
+    pub ice_shards: u32,
+    /// How much faster ice shards scatter than other pieces.
+    pub ice_shard_speed_multiplier: f32,
+    /// Explosive asteroids destroy other asteroids within this distance.
+    pub explosive_radius: f32,
+    /// Damage an explosive asteroid deals to the ship within its radius.
+    pub explosive_damage: f32,
+    /// Chance that a destroyed crystalline asteroid drops a supply crate.
+    pub crystalline_drop_chance: f32,
+}
+
+impl Default for AsteroidTuning {
+    fn default() -> Self {
+        Self {
+            generate_distance: 3000.0,
+            despawn_distance: 10000.0,
+            restitution: 0.8,
+            spin_transfer: 0.3,
+            break_energy: 20000.0,
+            fragment_speed: 60.0,
+            impact_speed: 40.0,
+            metallic_hit_points: 3,
+            ice_shards: 5,
+            ice_shard_speed_multiplier: 2.0,
+            explosive_radius: 250.0,
+            explosive_damage: 30.0,
+            crystalline_drop_chance: 0.5,
+        }
+    }
+}
+
+/// How thrust moves the ship.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlightModel {
+    /// The ship always moves where it faces, turning like a car.
+    #[default]
+    Arcade,
+    /// Thrust accelerates along the facing and the ship keeps drifting
+    /// while it turns.
+    Newtonian,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShipTuning {
+    pub flight_model: FlightModel,
+    pub max_speed: f32,
+    pub move_acceleration: f32,
+    /// Speed lost per second while coasting. In Newtonian flight this only
+    /// applies with flight assist on.
+    pub move_friction: f32,
+    /// Newtonian only: slows the ship down whenever it is not thrusting.
+    pub flight_assist: bool,
+    /// Newtonian only: acceleration of the reverse thruster.
+    pub reverse_acceleration: f32,
+    pub max_rotation_speed: f32,
+    pub rotation_acceleration: f32,
+    pub rotation_friction: f32,
+    pub lives: u32,
+    pub respawn_delay: f32,
+    pub invulnerability_duration: f32,
+    /// Asteroids closer than this to the respawn point are pushed away.
+    pub respawn_clear_radius: f32,
+    pub max_hull: f32,
+    pub max_shield: f32,
+    /// Seconds after a hit before the shield starts regenerating.
+    pub shield_regen_delay: f32,
+    /// Shield points regained per second.
+    pub shield_regen_rate: f32,
+    /// Damage per asteroid size, on top of the speed damage.
+    pub damage_per_size: f32,
+    /// Damage per asteroid size and unit of relative speed.
+    pub damage_per_speed: f32,
+    /// Speed at which a hit knocks the ship away from the asteroid.
+    pub knockback_speed: f32,
+    /// Seconds after a hit during which the ship cannot be hit again.
+    pub hit_invulnerability: f32,
+    /// Asteroids up to this size break on the ship; larger ones bounce it off.
+    pub max_breakable_size: u8,
+}
+
+impl Default for ShipTuning {
+    fn default() -> Self {
+        Self {
+            flight_model: FlightModel::Arcade,
+            max_speed: 300.0,
+            move_acceleration: 300.0,
+            move_friction: 100.0,
+            flight_assist: true,
+            reverse_acceleration: 150.0,
+            max_rotation_speed: 7.0,
+            rotation_acceleration: 14.0,
+            rotation_friction: 10.0,
+            lives: 3,
+            respawn_delay: 1.5,
+            invulnerability_duration: 3.0,
+            respawn_clear_radius: 300.0,
+            max_hull: 100.0,
+            max_shield: 50.0,
+            shield_regen_delay: 3.0,
+            shield_regen_rate: 10.0,
+            damage_per_size: 8.0,
+            damage_per_speed: 0.05,
+            knockback_speed: 250.0,
+            hit_invulnerability: 0.5,
+            max_breakable_size: 2,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RocketTuning {
+    pub guidance: Guidance,
+    /// Fastest turn, in radians per second.
+    pub turn_rate: f32,
+    /// How many times faster than the line of sight to its target a rocket
+    /// under proportional navigation turns.
+    pub navigation_gain: f32,
+    /// Seconds of thrust, after which the rocket stops steering and coasts.
+    pub fuel: f32,
+    /// Seconds a rocket coasts once out of fuel before it self-destructs.
+    pub ballistic_time: f32,
+    /// Distance from the target's edge at which the proximity fuse sets the
+    /// rocket off.
+    pub fuse_distance: f32,
+    /// Reach of the blast when a rocket detonates rather than hitting
+    /// something head on.
+    pub blast_radius: f32,
+}
+
+impl Default for RocketTuning {
+    fn default() -> Self {
+        Self {
+            guidance: Guidance::ProportionalNavigation,
+            turn_rate: 5.0,
+            navigation_gain: 4.0,
+            fuel: 3.0,
+            ballistic_time: 1.5,
+            fuse_distance: 20.0,
+            blast_radius: 100.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DroneTuning {
+    pub move_acceleration: f32,
+    pub max_speed: f32,
+    pub fast_max_speed: f32,
+    pub fire_distance: f32,
+    pub reload_time: f32,
+    pub uncharging_speed: f32,
+    pub fast_move_uncharging_speed: f32,
+    pub fire_charge_cost: f32,
+}
+
+impl Default for DroneTuning {
+    fn default() -> Self {
+        Self {
+            move_acceleration: 500.0,
+            max_speed: 120.0,
+            fast_max_speed: 600.0,
+            fire_distance: 500.0,
+            reload_time: 1.5,
+            uncharging_speed: 0.5,
+            fast_move_uncharging_speed: 2.5,
+            fire_charge_cost: 5.0,
+        }
+    }
+}
+
+/// Handling and weapons of one kind of enemy.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnemyKindTuning {
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub rotation_speed: f32,
+    /// Bullet hits the enemy takes before it is destroyed.
+    pub hit_points: u32,
+    /// Distance the enemy tries to keep from the ship.
+    pub preferred_distance: f32,
+    /// The enemy only opens fire this close to the ship; zero never fires.
+    pub fire_distance: f32,
+    pub reload_time: f32,
+    pub bullet_speed: f32,
+    pub bullet_damage: f32,
+    /// Largest angle, in radians, by which a shot may miss.
+    pub aim_spread: f32,
+    /// Damage dealt when the enemy crashes into the ship.
+    pub ram_damage: f32,
+}
+
+impl Default for EnemyKindTuning {
+    fn default() -> Self {
+        Self {
+            max_speed: 150.0,
+            acceleration: 200.0,
+            rotation_speed: 3.0,
+            hit_points: 3,
+            preferred_distance: 450.0,
+            fire_distance: 700.0,
+            reload_time: 1.2,
+            bullet_speed: 500.0,
+            bullet_damage: 10.0,
+            aim_spread: 0.25,
+            ram_damage: 20.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnemyTuning {
+    pub saucer: EnemyKindTuning,
+    pub interceptor: EnemyKindTuning,
+    pub sniper: EnemyKindTuning,
+}
+
+impl Default for EnemyTuning {
+    fn default() -> Self {
+        Self {
+            saucer: EnemyKindTuning::default(),
+            interceptor: EnemyKindTuning {
+                max_speed: 450.0,
+                acceleration: 400.0,
+                rotation_speed: 2.5,
+                hit_points: 1,
+                preferred_distance: 0.0,
+                fire_distance: 0.0,
+                reload_time: 0.0,
+                bullet_speed: 0.0,
+                bullet_damage: 0.0,
+                aim_spread: 0.0,
+                ram_damage: 40.0,
+            },
+            sniper: EnemyKindTuning {
+                max_speed: 120.0,
+                acceleration: 150.0,
+                rotation_speed: 2.0,
+                hit_points: 2,
+                preferred_distance: 900.0,
+                fire_distance: 1200.0,
+                reload_time: 2.5,
+                bullet_speed: 900.0,
+                bullet_damage: 20.0,
+                aim_spread: 0.02,
+                ram_damage: 20.0,
+            },
+        }
+    }
+}
+
+impl EnemyTuning {
+    pub fn kind(&self, kind: EnemyKind) -> &EnemyKindTuning {
+        match kind {
+            EnemyKind::Saucer => &self.saucer,
+            EnemyKind::Interceptor => &self.interceptor,
+            EnemyKind::Sniper => &self.sniper,
+        }
+    }
+}
+
+/// Attacks of one boss phase, which takes over once the boss's remaining
+/// health drops to `health_fraction` of its full health.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BossPhaseTuning {
+    pub health_fraction: f32,
+    pub speed: f32,
+    pub rotation_speed: f32,
+    pub reload_time: f32,
+    /// Bullets each turret fires at once, fanned `volley_spread` radians
+    /// apart.
+    pub volley: u32,
+    pub volley_spread: f32,
+    pub bullet_speed: f32,
+    pub bullet_damage: f32,
+}
+
+impl Default for BossPhaseTuning {
+    fn default() -> Self {
+        Self {
+            health_fraction: 1.0,
+            speed: 60.0,
+            rotation_speed: 0.3,
+            reload_time: 1.5,
+            volley: 1,
+            volley_spread: 0.0,
+            bullet_speed: 500.0,
+            bullet_damage: 10.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BossTuning {
+    /// A boss arrives with every wave whose number is a multiple of this,
+    /// and the next wave waits until it is destroyed; zero means no bosses.
+    pub every_waves: u32,
+    pub preferred_distance: f32,
+    pub fire_distance: f32,
+    pub turret_hit_points: u32,
+    pub shield_generator_hit_points: u32,
+    pub core_hit_points: u32,
+    /// Hit points a rocket or mine takes off the part it hits; bullets take
+    /// one.
+    pub rocket_damage: u32,
+    pub mine_damage: u32,
+    /// Damage dealt when the ship runs into the hull.
+    pub ram_damage: f32,
+    /// In order of falling `health_fraction`, starting with the phase the
+    /// boss arrives in.
+    pub phases: Vec<BossPhaseTuning>,
+}
+
+impl Default for BossTuning {
+    fn default() -> Self {
+        Self {
+            every_waves: 5,
+            preferred_distance: 600.0,
+            fire_distance: 900.0,
+            turret_hit_points: 8,
+            shield_generator_hit_points: 12,
+            core_hit_points: 30,
+            rocket_damage: 5,
+            mine_damage: 10,
+            ram_damage: 50.0,
+            phases: vec![
+                BossPhaseTuning::default(),
+                BossPhaseTuning {
+                    health_fraction: 0.6,
+                    speed: 40.0,
+                    rotation_speed: 0.6,
+                    reload_time: 1.2,
+                    volley: 3,
+                    volley_spread: 0.2,
+                    bullet_speed: 450.0,
+                    bullet_damage: 10.0,
+                },
+                BossPhaseTuning {
+                    health_fraction: 0.25,
+                    speed: 120.0,
+                    rotation_speed: 1.2,
+                    reload_time: 0.6,
+                    volley: 5,
+                    volley_spread: 0.25,
+                    bullet_speed: 600.0,
+                    bullet_damage: 10.0,
+                },
+            ],
+        }
+    }
+}
+
+impl BossTuning {
+    /// Phase at `index`, or the last one if the list got shorter.
+    pub fn phase(&self, index: usize) -> &BossPhaseTuning {
+        &self.phases[index.min(self.phases.len() - 1)]
+    }
+}
+
+/// Points for a destroyed asteroid are `points_per_size * size`, and for an
+/// enemy or boss part the points of its kind, all scaled by the weight of
+/// whatever destroyed it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScoreTuning {
+    pub points_per_size: f32,
+    pub saucer_points: f32,
+    pub interceptor_points: f32,
+    pub sniper_points: f32,
+    pub boss_part_points: f32,
+    pub boss_points: f32,
+    pub bullet_weight: f32,
+    pub rocket_weight: f32,
+    pub drone_weight: f32,
+    pub mine_weight: f32,
+    pub collision_weight: f32,
+    pub enemy_weight: f32,
+}
+
+impl Default for ScoreTuning {
+    fn default() -> Self {
+        Self {
+            points_per_size: 10.0,
+            saucer_points: 100.0,
+            interceptor_points: 75.0,
+            sniper_points: 150.0,
+            boss_part_points: 100.0,
+            boss_points: 1000.0,
+            bullet_weight: 1.0,
+            rocket_weight: 0.5,
+            drone_weight: 0.5,
+            mine_weight: 0.75,
+            collision_weight: 0.0,
+            enemy_weight: 0.0,
+        }
+    }
+}
+
+impl ScoreTuning {
+    pub fn weight(&self, cause: DestroyCause) -> f32 {
+        match cause {
+            DestroyCause::Bullet => self.bullet_weight,
+            DestroyCause::Rocket => self.rocket_weight,
+            DestroyCause::Drone => self.drone_weight,
+            DestroyCause::Mine => self.mine_weight,
+            DestroyCause::Collision => self.collision_weight,
+            DestroyCause::Enemy => self.enemy_weight,
+        }
+    }
+
+    pub fn enemy_points(&self, kind: EnemyKind) -> f32 {
+        match kind {
+            EnemyKind::Saucer => self.saucer_points,
+            EnemyKind::Interceptor => self.interceptor_points,
+            EnemyKind::Sniper => self.sniper_points,
+        }
+    }
+}
+
+/// One wave of asteroids: `asteroid_count` of them, one every
+/// `spawn_interval` seconds, with `enemy_count` enemies arriving among them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Wave {
+    pub asteroid_count: u32,
+    pub spawn_interval: f32,
+    pub speed: f32,
+    /// Relative chance of each asteroid size, starting at size 1.
+    pub size_weights: Vec<f32>,
+    /// Relative chance of each asteroid material.
+    pub material_weights: Vec<(Material, f32)>,
+    /// Enemies arriving among the asteroids, at most one with each.
+    pub enemy_count: u32,
+    /// Relative chance of each kind of enemy.
+    pub enemy_weights: Vec<(EnemyKind, f32)>,
+    /// Largest angle, in radians, by which an asteroid may miss the ship.
+    pub aim_spread: f32,
+}
+
+impl Default for Wave {
+    fn default() -> Self {
+        Self {
+            asteroid_count: 10,
+            spawn_interval: 1.0,
+            speed: 100.0,
+            size_weights: vec![1.0, 1.0, 1.0, 1.0],
+            material_weights: vec![(Material::Rock, 1.0)],
+            enemy_count: 0,
+            enemy_weights: vec![(EnemyKind::Saucer, 1.0)],
+            aim_spread: 0.2,
+        }
+    }
+}
+
+/// How waves past the last listed one keep getting harder; every extra
+/// wave applies these once more to the last listed wave.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EndlessTuning {
+    pub extra_asteroids: u32,
+    pub extra_enemies: u32,
+    pub interval_multiplier: f32,
+    pub speed_multiplier: f32,
+}
+
+impl Default for EndlessTuning {
+    fn default() -> Self {
+        Self {
+            extra_asteroids: 5,
+            extra_enemies: 1,
+            interval_multiplier: 0.9,
+            speed_multiplier: 1.1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WaveTuning {
+    pub break_duration: f32,
+    pub waves: Vec<Wave>,
+    pub endless: EndlessTuning,
+}
+
+impl Default for WaveTuning {
+    fn default() -> Self {
+        let wave = |asteroid_count, spawn_interval, speed, size_weights: &[f32], aim_spread| Wave {
+            asteroid_count,
+            spawn_interval,
+            speed,
+            size_weights: size_weights.to_vec(),
+            material_weights: vec![(Material::Rock, 1.0)],
+            enemy_count: 0,
+            enemy_weights: vec![(EnemyKind::Saucer, 1.0)],
+            aim_spread,
+        };
+        Self {
+            break_duration: 5.0,
+            waves: vec![
+                wave(10, 1.5, 80.0, &[1.0, 1.0], 0.3),
+                Wave {
+                    material_weights: vec![(Material::Rock, 1.0), (Material::Crystalline, 0.1)],
+                    ..wave(15, 1.2, 100.0, &[1.0, 1.0, 1.0], 0.3)
+                },
+                Wave {
+                    material_weights: vec![
+                        (Material::Rock, 1.0),
+                        (Material::Ice, 0.2),
+                        (Material::Crystalline, 0.1),
+                    ],
+                    enemy_count: 1,
+                    ..wave(20, 1.0, 110.0, &[1.0, 1.0, 1.0, 1.0], 0.2)
+                },
+                Wave {
+                    material_weights: vec![
+                        (Material::Rock, 1.0),
+                        (Material::Metallic, 0.2),
+                        (Material::Ice, 0.2),
+                        (Material::Explosive, 0.1),
+                        (Material::Crystalline, 0.1),
+                    ],
+                    enemy_count: 2,
+                    enemy_weights: vec![(EnemyKind::Saucer, 1.0), (EnemyKind::Interceptor, 1.0)],
+                    ..wave(25, 0.8, 130.0, &[0.5, 1.0, 1.0, 1.0], 0.15)
+                },
+                Wave {
+                    material_weights: vec![
+                        (Material::Rock, 1.0),
+                        (Material::Metallic, 0.3),
+                        (Material::Ice, 0.3),
+                        (Material::Explosive, 0.2),
+                        (Material::Crystalline, 0.1),
+                    ],
+                    enemy_count: 3,
+                    enemy_weights: vec![
+                        (EnemyKind::Saucer, 1.0),
+                        (EnemyKind::Interceptor, 1.0),
+                        (EnemyKind::Sniper, 1.0),
+                    ],
+                    ..wave(30, 0.6, 150.0, &[0.5, 1.0, 1.5, 1.5], 0.1)
+                },
+            ],
+            endless: EndlessTuning::default(),
+        }
+    }
+}
+
+impl WaveTuning {
+    /// Definition of wave `number`, counting from 1.
+    pub fn wave(&self, number: u32) -> Wave {
+        let index = number.saturating_sub(1) as usize;
+        if let Some(wave) = self.waves.get(index) {
+            return wave.clone();
+        }
+        let mut wave = self.waves.last().cloned().unwrap_or_default();
+        let extra = (index + 1 - self.waves.len()) as u32;
+        let endless = &self.endless;
+        wave.asteroid_count += endless.extra_asteroids * extra;
+        wave.enemy_count += endless.extra_enemies * extra;
+        wave.spawn_interval *= endless.interval_multiplier.powi(extra as i32);
+        wave.speed *= endless.speed_multiplier.powi(extra as i32);
+        wave
+    }
+}
+
+/// Crates dropped by destroyed asteroids.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PickupTuning {
+    /// Seconds a crate stays before it disappears.
+    pub lifetime: f32,
+    pub supply_rockets: u32,
+    pub supply_drones: u32,
+    pub supply_mines: u32,
+    /// What an asteroid drops, one table per size starting at size 1;
+    /// larger asteroids use the last table.
+    pub drops: Vec<DropTable>,
+}
+
+impl Default for PickupTuning {
+    fn default() -> Self {
+        use PickupKind::*;
+        let table = |chance, kinds: &[(PickupKind, f32)]| DropTable {
+            chance,
+            kinds: kinds.to_vec(),
+        };
+        Self {
+            lifetime: 20.0,
+            supply_rockets: 5,
+            supply_drones: 1,
+            supply_mines: 3,
+            drops: vec![
+                table(0.08, &[(Supply, 3.0), (RapidFire, 1.0), (SpreadShot, 1.0)]),
+                table(
+                    0.12,
+                    &[
+                        (Supply, 3.0),
+                        (RapidFire, 1.0),
+                        (SpreadShot, 1.0),
+                        (DroneRecharge, 1.0),
+                    ],
+                ),
+                table(
+                    0.18,
+                    &[
+                        (Supply, 2.0),
+                        (RapidFire, 1.0),
+                        (SpreadShot, 1.0),
+                        (Shield, 1.0),
+                        (DroneRecharge, 1.0),
+                        (TripleRockets, 1.0),
+                    ],
+                ),
+                table(
+                    0.25,
+                    &[
+                        (Supply, 2.0),
+                        (RapidFire, 1.0),
+                        (SpreadShot, 1.0),
+                        (Shield, 1.5),
+                        (DroneRecharge, 1.0),
+                        (TripleRockets, 1.5),
+                    ],
+                ),
+            ],
+        }
+    }
+}
+
+impl PickupTuning {
+    pub fn drop_table(&self, size: u8) -> Option<&DropTable> {
+        let index = (size.max(1) - 1) as usize;
+        self.drops.get(index).or(self.drops.last())
+    }
+}
+
+/// Chance, from 0 to 1, of dropping anything, and the relative weight of
+/// each kind of crate when something drops.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DropTable {
+    pub chance: f32,
+    pub kinds: Vec<(PickupKind, f32)>,
+}
+
+impl Default for DropTable {
+    fn default() -> Self {
+        Self {
+            chance: 0.15,
+            kinds: vec![(PickupKind::Supply, 1.0)],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PowerUpTuning {
+    pub rapid_fire_duration: f32,
+    pub spread_shot_duration: f32,
+    pub shield_duration: f32,
+    pub drone_recharge_duration: f32,
+    pub triple_rockets_duration: f32,
+    /// Bullet reload is multiplied by this during rapid fire.
+    pub rapid_fire_reload_multiplier: f32,
+    pub spread_shot_bullets: u32,
+    /// Angle, in radians, between neighbouring bullets of a spread shot.
+    pub spread_shot_angle: f32,
+    /// Angle, in radians, between neighbouring triple rockets.
+    pub triple_rockets_angle: f32,
+}
+
+impl Default for PowerUpTuning {
+    fn default() -> Self {
+        Self {
+            rapid_fire_duration: 10.0,
+            spread_shot_duration: 10.0,
+            shield_duration: 8.0,
+            drone_recharge_duration: 15.0,
+            triple_rockets_duration: 12.0,
+            rapid_fire_reload_multiplier: 0.35,
+            spread_shot_bullets: 3,
+            spread_shot_angle: 0.15,
+            triple_rockets_angle: 0.3,
+        }
+    }
+}
+
+impl PowerUpTuning {
+    /// How long the power-up lasts; zero for crates that act instantly.
+    pub fn duration(&self, kind: PickupKind) -> f32 {
+        match kind {
+            PickupKind::Supply => 0.0,
+            PickupKind::RapidFire => self.rapid_fire_duration,
+            PickupKind::SpreadShot => self.spread_shot_duration,
+            PickupKind::Shield => self.shield_duration,
+            PickupKind::DroneRecharge => self.drone_recharge_duration,
+            PickupKind::TripleRockets => self.triple_rockets_duration,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TuningError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for TuningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TuningError::Io(error) => write!(f, "{}", error),
+            TuningError::Parse(error) => write!(f, "{}", error),
+            TuningError::Invalid(problems) => write!(f, "{}", problems.join("; ")),
+        }
+    }
+}
+
+impl std::error::Error for TuningError {}
+
+struct Validator {
+    problems: Vec<String>,
+}
+
+impl Validator {
+    fn positive(&mut self, name: &str, value: f32) {
+        if !(value.is_finite() && value > 0.0) {
+            self.problems
+                .push(format!("{} must be greater than zero, got {}", name, value));
+        }
+    }
+
+    fn non_negative(&mut self, name: &str, value: f32) {
+        if !(value.is_finite() && value >= 0.0) {
+            self.problems
+                .push(format!("{} must not be negative, got {}", name, value));
+        }
+    }
+
+    fn fraction(&mut self, name: &str, value: f32) {
+        if !(0.0..=1.0).contains(&value) {
+            self.problems
+                .push(format!("{} must be between 0 and 1, got {}", name, value));
+        }
+    }
+}
+
+impl Tuning {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TuningError> {
+        let text = fs::read_to_string(path).map_err(TuningError::Io)?;
+        let tuning: Tuning = ron::from_str(&text).map_err(TuningError::Parse)?;
+        tuning.validate()?;
+        Ok(tuning)
+    }
+
+    /// Like `load`, but a missing file means the built-in defaults.
+    pub fn load_or_default(path: impl AsRef<Path>) -> Result<Self, TuningError> {
+        match Self::load(path) {
+            Err(TuningError::Io(error)) if error.kind() == io::ErrorKind::NotFound => {
+                Ok(Self::default())
+            }
+            result => result,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), TuningError> {
+        let mut validator = Validator {
+            problems: Vec::new(),
+        };
+
+        let weapons = &self.weapons;
+        validator.non_negative("weapons.bullet_reload", weapons.bullet_reload);
+        validator.non_negative("weapons.rocket_reload", weapons.rocket_reload);
+        validator.non_negative("weapons.drone_reload", weapons.drone_reload);
+        validator.non_negative("weapons.mine_reload", weapons.mine_reload);
+        validator.non_negative("weapons.rocket_lock_angle", weapons.rocket_lock_angle);
+        validator.non_negative("weapons.rocket_lock_distance", weapons.rocket_lock_distance);
+        for (name, starting, max) in [
+            ("rockets", weapons.starting_rockets, weapons.max_rockets),
+            ("drones", weapons.starting_drones, weapons.max_drones),
+            ("mines", weapons.starting_mines, weapons.max_mines),
+        ] {
+            if starting > max {
+                validator.problems.push(format!(
+                    "weapons.starting_{} ({}) must not exceed weapons.max_{} ({})",
+                    name, starting, name, max
+                ));
+            }
+        }
+
+        let asteroids = &self.asteroids;
+        validator.positive("asteroids.generate_distance", asteroids.generate_distance);
+        validator.positive("asteroids.despawn_distance", asteroids.despawn_distance);
+        validator.fraction("asteroids.restitution", asteroids.restitution);
+        validator.fraction("asteroids.spin_transfer", asteroids.spin_transfer);
+        validator.non_negative("asteroids.break_energy", asteroids.break_energy);
+        validator.non_negative("asteroids.fragment_speed", asteroids.fragment_speed);
+        validator.non_negative("asteroids.impact_speed", asteroids.impact_speed);
+        if asteroids.metallic_hit_points == 0 {
+            validator
+                .problems
+                .push("asteroids.metallic_hit_points must be at least 1".to_string());
+        }
+        if asteroids.ice_shards < 2 {
+            validator
+                .problems
+                .push("asteroids.ice_shards must be at least 2".to_string());
+        }
+        validator.positive(
+            "asteroids.ice_shard_speed_multiplier",
+            asteroids.ice_shard_speed_multiplier,
+        );
+        validator.non_negative("asteroids.explosive_radius", asteroids.explosive_radius);
+        validator.non_negative("asteroids.explosive_damage", asteroids.explosive_damage);
+        validator.fraction(
+            "asteroids.crystalline_drop_chance",
+            asteroids.crystalline_drop_chance,
+        );
+        if asteroids.despawn_distance <= asteroids.generate_distance {
+            validator.problems.push(format!(
+                "asteroids.despawn_distance ({}) must be greater than asteroids.generate_distance ({})",
+                asteroids.despawn_distance, asteroids.generate_distance
+            ));
+        }
+
+        let ship = &self.ship;
+        validator.positive("ship.max_speed", ship.max_speed);
+        validator.positive("ship.move_acceleration", ship.move_acceleration);
+        validator.non_negative("ship.move_friction", ship.move_friction);
+        validator.non_negative("ship.reverse_acceleration", ship.reverse_acceleration);
+        validator.positive("ship.max_rotation_speed", ship.max_rotation_speed);
+        validator.positive("ship.rotation_acceleration", ship.rotation_acceleration);
+        validator.non_negative("ship.rotation_friction", ship.rotation_friction);
+        if ship.lives == 0 {
+            validator
+                .problems
+                .push("ship.lives must be at least 1".to_string());
+        }
+        validator.non_negative("ship.respawn_delay", ship.respawn_delay);
+        validator.non_negative(
+            "ship.invulnerability_duration",
+            ship.invulnerability_duration,
+        );
+        validator.non_negative("ship.respawn_clear_radius", ship.respawn_clear_radius);
+        validator.positive("ship.max_hull", ship.max_hull);
+        validator.non_negative("ship.max_shield", ship.max_shield);
+        validator.non_negative("ship.shield_regen_delay", ship.shield_regen_delay);
+        validator.non_negative("ship.shield_regen_rate", ship.shield_regen_rate);
+        validator.non_negative("ship.damage_per_size", ship.damage_per_size);
+        validator.non_negative("ship.damage_per_speed", ship.damage_per_speed);
+        validator.non_negative("ship.knockback_speed", ship.knockback_speed);
+        validator.non_negative("ship.hit_invulnerability", ship.hit_invulnerability);
+
+        let rocket = &self.rocket;
+        validator.positive("rocket.turn_rate", rocket.turn_rate);
+        validator.non_negative("rocket.navigation_gain", rocket.navigation_gain);
+        validator.non_negative("rocket.fuel", rocket.fuel);
+        validator.non_negative("rocket.ballistic_time", rocket.ballistic_time);
+        validator.non_negative("rocket.fuse_distance", rocket.fuse_distance);
+        validator.non_negative("rocket.blast_radius", rocket.blast_radius);
+
+        let drone = &self.drone;
+        validator.positive("drone.move_acceleration", drone.move_acceleration);
+        validator.positive("drone.max_speed", drone.max_speed);
+        validator.positive("drone.fast_max_speed", drone.fast_max_speed);
+        validator.non_negative("drone.fire_distance", drone.fire_distance);
+        validator.non_negative("drone.reload_time", drone.reload_time);
+        validator.non_negative("drone.uncharging_speed", drone.uncharging_speed);
+        validator.non_negative(
+            "drone.fast_move_uncharging_speed",
+            drone.fast_move_uncharging_speed,
+        );
+        validator.non_negative("drone.fire_charge_cost", drone.fire_charge_cost);
+
+        for (kind, enemy) in [
+            ("saucer", &self.enemies.saucer),
+            ("interceptor", &self.enemies.interceptor),
+            ("sniper", &self.enemies.sniper),
+        ] {
+            let name = |field: &str| format!("enemies.{}.{}", kind, field);
+            validator.positive(&name("max_speed"), enemy.max_speed);
+            validator.positive(&name("acceleration"), enemy.acceleration);
+            validator.positive(&name("rotation_speed"), enemy.rotation_speed);
+            if enemy.hit_points == 0 {
+                validator
+                    .problems
+                    .push(format!("{} must be at least 1", name("hit_points")));
+            }
+            validator.non_negative(&name("preferred_distance"), enemy.preferred_distance);
+            validator.non_negative(&name("fire_distance"), enemy.fire_distance);
+            validator.non_negative(&name("reload_time"), enemy.reload_time);
+            if enemy.fire_distance > 0.0 {
+                validator.positive(&name("bullet_speed"), enemy.bullet_speed);
+            } else {
+                validator.non_negative(&name("bullet_speed"), enemy.bullet_speed);
+            }
+            validator.non_negative(&name("bullet_damage"), enemy.bullet_damage);
+            validator.non_negative(&name("aim_spread"), enemy.aim_spread);
+            validator.non_negative(&name("ram_damage"), enemy.ram_damage);
+        }
+
+        let boss = &self.boss;
+        validator.non_negative("boss.preferred_distance", boss.preferred_distance);
+        validator.non_negative("boss.fire_distance", boss.fire_distance);
+        for (name, hit_points) in [
+            ("boss.turret_hit_points", boss.turret_hit_points),
+            (
+                "boss.shield_generator_hit_points",
+                boss.shield_generator_hit_points,
+            ),
+            ("boss.core_hit_points", boss.core_hit_points),
+            ("boss.rocket_damage", boss.rocket_damage),
+            ("boss.mine_damage", boss.mine_damage),
+        ] {
+            if hit_points == 0 {
+                validator
+                    .problems
+                    .push(format!("{} must be at least 1", name));
+            }
+        }
+        validator.non_negative("boss.ram_damage", boss.ram_damage);
+        if boss.phases.is_empty() {
+            validator
+                .problems
+                .push("boss.phases must list at least one phase".to_string());
+        }
+        for (index, phase) in boss.phases.iter().enumerate() {
+            let name = |field: &str| format!("boss.phases[{}].{}", index, field);
+            validator.fraction(&name("health_fraction"), phase.health_fraction);
+            if index > 0 && phase.health_fraction > boss.phases[index - 1].health_fraction {
+                validator.problems.push(format!(
+                    "{} must not be above the previous phase's",
+                    name("health_fraction")
+                ));
+            }
+            validator.non_negative(&name("speed"), phase.speed);
+            validator.non_negative(&name("rotation_speed"), phase.rotation_speed);
+            validator.non_negative(&name("reload_time"), phase.reload_time);
+            if phase.volley == 0 {
+                validator
+                    .problems
+                    .push(format!("{} must be at least 1", name("volley")));
+            }
+            validator.non_negative(&name("volley_spread"), phase.volley_spread);
+            validator.positive(&name("bullet_speed"), phase.bullet_speed);
+            validator.non_negative(&name("bullet_damage"), phase.bullet_damage);
+        }
+
+        let score = &self.score;
+        validator.non_negative("score.points_per_size", score.points_per_size);
+        validator.non_negative("score.saucer_points", score.saucer_points);
+        validator.non_negative("score.interceptor_points", score.interceptor_points);
+        validator.non_negative("score.sniper_points", score.sniper_points);
+        validator.non_negative("score.boss_part_points", score.boss_part_points);
+        validator.non_negative("score.boss_points", score.boss_points);
+        validator.non_negative("score.bullet_weight", score.bullet_weight);
+        validator.non_negative("score.rocket_weight", score.rocket_weight);
+        validator.non_negative("score.drone_weight", score.drone_weight);
+        validator.non_negative("score.mine_weight", score.mine_weight);
+        validator.non_negative("score.collision_weight", score.collision_weight);
+        validator.non_negative("score.enemy_weight", score.enemy_weight);
+
+        let waves = &self.waves;
+        validator.non_negative("waves.break_duration", waves.break_duration);
+        if waves.waves.is_empty() {
+            validator
+                .problems
+                .push("waves.waves must list at least one wave".to_string());
+        }
+        for (index, wave) in waves.waves.iter().enumerate() {
+            let name = |field: &str| format!("waves.waves[{}].{}", index, field);
+            if wave.asteroid_count == 0 {
+                validator.problems.push(format!(
+                    "{} must be greater than zero",
+                    name("asteroid_count")
+                ));
+            }
+            validator.positive(&name("spawn_interval"), wave.spawn_interval);
+            validator.positive(&name("speed"), wave.speed);
+            validator.non_negative(&name("aim_spread"), wave.aim_spread);
+            if wave.size_weights.is_empty() || wave.size_weights.len() > MAX_ASTEROID_SIZE as usize
+            {
+                validator.problems.push(format!(
+                    "{} must list between 1 and {} weights",
+                    name("size_weights"),
+                    MAX_ASTEROID_SIZE
+                ));
+            }
+            for weight in &wave.size_weights {
+                validator.non_negative(&name("size_weights"), *weight);
+            }
+            if wave.size_weights.iter().sum::<f32>() <= 0.0 {
+                validator.problems.push(format!(
+                    "{} must have at least one weight above zero",
+                    name("size_weights")
+                ));
+            }
+            for (_, weight) in &wave.material_weights {
+                validator.non_negative(&name("material_weights"), *weight);
+            }
+            if wave
+                .material_weights
+                .iter()
+                .map(|(_, weight)| weight)
+                .sum::<f32>()
+                <= 0.0
+            {
+                validator.problems.push(format!(
+                    "{} must have at least one weight above zero",
+                    name("material_weights")
+                ));
+            }
+            for (_, weight) in &wave.enemy_weights {
+                validator.non_negative(&name("enemy_weights"), *weight);
+            }
+            // Enemies arrive alongside asteroids, at most one each.
+            if wave.enemy_count > wave.asteroid_count {
+                validator.problems.push(format!(
+                    "{} ({}) must not exceed {} ({})",
+                    name("enemy_count"),
+                    wave.enemy_count,
+                    name("asteroid_count"),
+                    wave.asteroid_count
+                ));
+            }
+            if wave.enemy_count > 0
+                && wave
+                    .enemy_weights
+                    .iter()
+                    .map(|(_, weight)| weight)
+                    .sum::<f32>()
+                    <= 0.0
+            {
+                validator.problems.push(format!(
+                    "{} must have at least one weight above zero",
+                    name("enemy_weights")
+                ));
+            }
+        }
+        if waves.endless.extra_enemies > waves.endless.extra_asteroids {
+            validator.problems.push(format!(
+                "waves.endless.extra_enemies ({}) must not exceed waves.endless.extra_asteroids ({})",
+                waves.endless.extra_enemies, waves.endless.extra_asteroids
+            ));
+        }
+        validator.positive(
+            "waves.endless.interval_multiplier",
+            waves.endless.interval_multiplier,
+        );
+        validator.positive(
+            "waves.endless.speed_multiplier",
+            waves.endless.speed_multiplier,
+        );
+
+        let pickups = &self.pickups;
+        validator.positive("pickups.lifetime", pickups.lifetime);
+        for (index, table) in pickups.drops.iter().enumerate() {
+            let name = format!("pickups.drops[{}]", index);
+            validator.fraction(&format!("{}.chance", name), table.chance);
+            for (_, weight) in &table.kinds {
+                validator.non_negative(&format!("{}.kinds weight", name), *weight);
+            }
+            if table.chance > 0.0
+                && table.kinds.iter().map(|(_, weight)| weight).sum::<f32>() <= 0.0
+            {
+                validator.problems.push(format!(
+                    "{}.kinds must have at least one weight above zero",
+                    name
+                ));
+            }
+        }
+
+        let power_ups = &self.power_ups;
+        validator.positive(
+            "power_ups.rapid_fire_duration",
+            power_ups.rapid_fire_duration,
+        );
+        validator.positive(
+            "power_ups.spread_shot_duration",
+            power_ups.spread_shot_duration,
+        );
+        validator.positive("power_ups.shield_duration", power_ups.shield_duration);
+        validator.positive(
+            "power_ups.drone_recharge_duration",
+            power_ups.drone_recharge_duration,
+        );
+        validator.positive(
+            "power_ups.triple_rockets_duration",
+            power_ups.triple_rockets_duration,
+        );
+        validator.positive(
+            "power_ups.rapid_fire_reload_multiplier",
+            power_ups.rapid_fire_reload_multiplier,
+        );
+        if power_ups.spread_shot_bullets == 0 {
+            validator
+                .problems
+                .push("power_ups.spread_shot_bullets must be at least 1".to_string());
+        }
+        validator.non_negative("power_ups.spread_shot_angle", power_ups.spread_shot_angle);
+        validator.non_negative(
+            "power_ups.triple_rockets_angle",
+            power_ups.triple_rockets_angle,
+        );
+
+        if validator.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(TuningError::Invalid(validator.problems))
+        }
+    }
+}
+
+/// Notices when the tuning file changes on disk, so balance can be edited
+/// while the game is running.
+pub struct TuningWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl TuningWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self {
+            modified: modified_time(&path),
+            path,
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Reloads the file if it changed since the last call; `None` means
+    /// nothing changed.
+    pub fn poll(&mut self) -> Option<Result<Tuning, TuningError>> {
+        if self.last_check.elapsed() < WATCH_INTERVAL {
+            return None;
+        }
+        self.last_check = Instant::now();
+
+        let modified = modified_time(&self.path);
+        if modified.is_none() || modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(Tuning::load(&self.path))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(tuning: &Tuning) -> Vec<String> {
+        match tuning.validate() {
+            Err(TuningError::Invalid(problems)) => problems,
+            other => panic!("expected invalid tuning, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Tuning::default().validate().is_ok());
+    }
+
+    #[test]
+    fn shipped_file_is_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tuning.ron");
+        assert!(Tuning::load(path).is_ok());
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        let mut tuning = Tuning::default();
+        tuning.weapons.bullet_reload = -1.0;
+        tuning.drone.max_speed = f32::NAN;
+        tuning.waves.waves[0].asteroid_count = 0;
+        tuning.waves.waves[1].enemy_count = tuning.waves.waves[1].asteroid_count + 1;
+        let problems = problems(&tuning);
+        for name in [
+            "weapons.bullet_reload",
+            "drone.max_speed",
+            "waves.waves[0].asteroid_count",
+            "waves.waves[1].enemy_count",
+        ] {
+            assert!(
+                problems.iter().any(|problem| problem.starts_with(name)),
+                "no problem reported for {}: {:?}",
+                name,
+                problems
+            );
+        }
+    }
+}